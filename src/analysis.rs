@@ -0,0 +1,514 @@
+//! Tools for inspecting and manipulating Playfair squares.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::{PairCase, PlayfairCipher};
+
+/// Exchange of two cells of the square, each given as a 0-based `(row, col)` pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellSwap {
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+}
+
+impl CellSwap {
+    /// Returns a copy of `cipher` whose square has the two cells exchanged.
+    pub fn apply(&self, cipher: &PlayfairCipher) -> PlayfairCipher {
+        let mut square = cipher.square();
+        square.swap(
+            self.first.0 * 5 + self.first.1,
+            self.second.0 * 5 + self.second.1,
+        );
+        cipher.with_square(&square)
+    }
+}
+
+/// Error returned by [`edits_for_target`] if no set of at most
+/// [`MAX_SWAPS`] cell swaps makes the ciphertext decode to the target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EditsNotFound;
+
+impl fmt::Display for EditsNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "no set of at most {} cell swaps decodes to the target",
+            MAX_SWAPS
+        )
+    }
+}
+
+impl std::error::Error for EditsNotFound {}
+
+/// Largest number of swaps tried by [`edits_for_target`].
+pub const MAX_SWAPS: usize = 2;
+
+/// Finds a smallest set of cell swaps after which `ciphertext` decodes to `target`.
+///
+/// All combinations of up to [`MAX_SWAPS`] swaps are tried, fewest first.
+pub fn edits_for_target(
+    cipher: &PlayfairCipher,
+    ciphertext: &str,
+    target: &str,
+) -> Result<Vec<CellSwap>, EditsNotFound> {
+    let decodes_to_target = |cipher: &PlayfairCipher| match cipher.decode(ciphertext) {
+        Ok(plaintext) => plaintext == target,
+        Err(_) => false,
+    };
+    if decodes_to_target(cipher) {
+        return Ok(Vec::new());
+    }
+
+    let mut swaps = Vec::with_capacity(300);
+    for a in 0..25 {
+        for b in a + 1..25 {
+            swaps.push(CellSwap {
+                first: (a / 5, a % 5),
+                second: (b / 5, b % 5),
+            });
+        }
+    }
+    for swap in &swaps {
+        if decodes_to_target(&swap.apply(cipher)) {
+            return Ok(vec![*swap]);
+        }
+    }
+    for (i, first) in swaps.iter().enumerate() {
+        let once = first.apply(cipher);
+        for second in &swaps[i + 1..] {
+            if decodes_to_target(&second.apply(&once)) {
+                return Ok(vec![*first, *second]);
+            }
+        }
+    }
+    Err(EditsNotFound)
+}
+
+/// Encodes `text` with every possible pad letter ('a' to 'z' without 'j')
+/// and returns the distinct ciphertexts, each with the first pad producing it.
+pub fn pad_variants(cipher: &PlayfairCipher, text: &str) -> Vec<(char, String)> {
+    let mut variants: Vec<(char, String)> = Vec::new();
+    for pad in ('a'..='z').filter(|&letter| letter != 'j') {
+        let ciphertext = match cipher.clone().with_pad(pad).encode(text) {
+            Ok(ciphertext) => ciphertext,
+            Err(_) => continue,
+        };
+        if variants.iter().all(|(_, known)| *known != ciphertext) {
+            variants.push((pad, ciphertext));
+        }
+    }
+    variants
+}
+
+/// Returns the edges between each letter and its right and lower neighbour,
+/// wrapping around the edges of the square.
+///
+/// Every pair of row or column neighbours appears exactly once, so each
+/// letter is part of four edges.
+pub fn adjacency(cipher: &PlayfairCipher) -> Vec<(char, char)> {
+    let square = cipher.square();
+    let mut edges = Vec::with_capacity(50);
+    for row in 0..5 {
+        for col in 0..5 {
+            let letter = square[row * 5 + col] as char;
+            edges.push((letter, square[row * 5 + (col + 1) % 5] as char));
+            edges.push((letter, square[(row + 1) % 5 * 5 + col] as char));
+        }
+    }
+    edges
+}
+
+/// Counts how many digraphs of `text` are enciphered by the same-row, the
+/// same-column and the rectangle rule, in this order.
+pub fn case_distribution(cipher: &PlayfairCipher, text: &str) -> (usize, usize, usize) {
+    let mut counts = (0, 0, 0);
+    for (a, b) in cipher.digraphs(text) {
+        let a = PlayfairCipher::letter_index(a as u8).unwrap();
+        let b = PlayfairCipher::letter_index(b as u8).unwrap();
+        match cipher.classify_pair(a, b) {
+            PairCase::SameRow => counts.0 += 1,
+            PairCase::SameColumn => counts.1 += 1,
+            PairCase::Rectangle => counts.2 += 1,
+            PairCase::SameCell => {}
+        }
+    }
+    counts
+}
+
+/// Returns the letters which can appear in ciphertexts of `cipher`, that is
+/// the letters of its square. A well-formed square yields all letters but 'j'.
+pub fn reachable_outputs(cipher: &PlayfairCipher) -> HashSet<char> {
+    cipher
+        .square()
+        .iter()
+        .map(|&letter| letter as char)
+        .collect()
+}
+
+/// Encodes every word of `words` and maps it to its ciphertext, to look up
+/// known plaintext fragments in an observed ciphertext.
+pub fn crib_map(cipher: &PlayfairCipher, words: &[&str]) -> HashMap<String, String> {
+    words
+        .iter()
+        .filter_map(|&word| Some((word.to_string(), cipher.encode(word).ok()?)))
+        .collect()
+}
+
+/// Rough estimate of how hard it is to recover the key of a ciphertext by
+/// statistical means.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// Estimates the cracking difficulty of a ciphertext with `ciphertext_len`
+/// letters.
+///
+/// The estimate only looks at how well digraph frequencies can be counted.
+/// Below 100 letters there are fewer than 50 digraphs for 600 possible ones,
+/// so hardly any digraph repeats and frequencies cannot guide a search: the
+/// estimate is [`Difficulty::Hard`]. Up to 300 letters the most common
+/// digraphs start to stand out ([`Difficulty::Medium`]), and beyond that
+/// they can be matched against the language reliably ([`Difficulty::Easy`]).
+pub fn crack_difficulty(ciphertext_len: usize) -> Difficulty {
+    if ciphertext_len < 100 {
+        Difficulty::Hard
+    } else if ciphertext_len < 300 {
+        Difficulty::Medium
+    } else {
+        Difficulty::Easy
+    }
+}
+
+/// Counts the distinct ciphertext digraphs produced from the 600 plaintext
+/// digraphs of two different letters.
+///
+/// Since decoding inverts encoding, every well-formed square yields all 600.
+pub fn distinct_output_digraphs(cipher: &PlayfairCipher) -> usize {
+    let (forward, _) = cipher.encode_lookup_tables();
+    let mut outputs = HashSet::new();
+    for a in 0..25 {
+        for b in (0..25).filter(|&b| b != a) {
+            let i = 2 * (25 * a + b);
+            outputs.insert((forward[i], forward[i + 1]));
+        }
+    }
+    outputs.len()
+}
+
+/// Lists the grid keys (see [`PlayfairCipher::from_grid`]) which differ only
+/// in writing 'i' or 'j' at the shared cell, all giving the same cipher.
+pub fn ij_equivalent_keys(cipher: &PlayfairCipher) -> Vec<String> {
+    let grid = cipher.grid();
+    let with_j = grid.replace('i', "j");
+    vec![grid, with_j]
+}
+
+/// Tells whether the keys `key1` and `key2` give the same square, as e.g.
+/// "hello" and "helo" do since repeated letters are skipped.
+pub fn grids_equal(key1: &str, key2: &str) -> bool {
+    PlayfairCipher::new(key1) == PlayfairCipher::new(key2)
+}
+
+/// Counts each letter 'a' to 'z' in `text`, normalized the way
+/// [`PlayfairCipher::from_mnemonic`] normalizes its words: uppercase letters
+/// count as lowercase and other characters are skipped. Unlike in the
+/// square, 'j' is counted on its own and not folded into 'i'.
+pub fn letter_histogram(text: &str) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for letter in PlayfairCipher::normalize_key(text).bytes() {
+        counts[(letter - b'a') as usize] += 1;
+    }
+    counts
+}
+
+/// Decodes `ciphertext` and lists the plaintexts for the plausible ways
+/// fillers may have been inserted, for a human to choose from:
+///
+/// 1. no filler removed,
+/// 2. fillers between two equal letters removed,
+/// 3. a trailing filler removed,
+/// 4. both of the above,
+/// 5. every filler at the second position of a digraph removed.
+///
+/// A filler is a letter `encode` could have inserted at its position: the
+/// pad, 'q' after the pad ('x' if 'q' is the pad), or the pad of that
+/// position if the cipher alternates between pads (see
+/// [`PlayfairCipher::with_pads`]).
+///
+/// Duplicates are left out, so the list has at most five entries.
+pub fn all_unpaddings(cipher: &PlayfairCipher, ciphertext: &str) -> Vec<String> {
+    let decoded = match cipher.decode(ciphertext) {
+        Ok(decoded) => decoded,
+        Err(_) => return Vec::new(),
+    };
+    let letters: Vec<u8> = decoded
+        .bytes()
+        .filter_map(PlayfairCipher::letter_index)
+        .collect();
+    // Only the second letter of a digraph can be a filler.
+    let is_pad = |i: usize| {
+        i % 2 == 1
+            && letters[i]
+                == PlayfairCipher::filler_at(cipher.pad, &cipher.pads, i / 2, letters[i - 1])
+    };
+    let between_doubles: Vec<bool> = (0..letters.len())
+        .map(|i| is_pad(i) && i + 1 < letters.len() && letters[i - 1] == letters[i + 1])
+        .collect();
+    let trailing: Vec<bool> = (0..letters.len())
+        .map(|i| is_pad(i) && i + 1 == letters.len())
+        .collect();
+    let both: Vec<bool> = between_doubles
+        .iter()
+        .zip(&trailing)
+        .map(|(&a, &b)| a || b)
+        .collect();
+    let every: Vec<bool> = (0..letters.len()).map(is_pad).collect();
+
+    let mut candidates: Vec<String> = Vec::new();
+    for mask in [vec![], between_doubles, trailing, both, every] {
+        if let Ok(candidate) = cipher.decode_unpadded_exact(ciphertext, &mask) {
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates
+}
+
+/// Counts the digraphs of the ciphertext of `text`.
+pub fn digraph_counts(cipher: &PlayfairCipher, text: &str) -> HashMap<(char, char), usize> {
+    let mut counts = HashMap::new();
+    let ciphertext = match cipher.encode(text) {
+        Ok(ciphertext) => ciphertext,
+        Err(_) => return counts,
+    };
+    let letters: Vec<char> = ciphertext
+        .chars()
+        .filter(char::is_ascii_lowercase)
+        .collect();
+    for pair in letters.chunks_exact(2) {
+        *counts.entry((pair[0], pair[1])).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Returns the Shannon entropy in bits of the digraph distribution of the
+/// ciphertext of `text`, between 0 (a single repeated digraph) and
+/// log2(600) for a perfectly uniform one.
+pub fn digraph_entropy(cipher: &PlayfairCipher, text: &str) -> f64 {
+    let counts = digraph_counts(cipher, text);
+    let total = counts.values().sum::<usize>() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[test]
+fn test_edits_for_target_single_swap() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let designed = CellSwap {
+        first: (0, 0),
+        second: (3, 2),
+    }
+    .apply(&cipher);
+    let ciphertext = designed.encode("code").unwrap();
+    assert_ne!(cipher.decode(&ciphertext).unwrap(), "code");
+
+    let swaps = edits_for_target(&cipher, &ciphertext, "code").unwrap();
+    assert_eq!(swaps.len(), 1);
+    assert_eq!(swaps[0].apply(&cipher).decode(&ciphertext).unwrap(), "code");
+}
+
+#[test]
+fn test_edits_for_target_unchanged() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let swaps = edits_for_target(&cipher, "bmod", "hide").unwrap();
+    assert!(swaps.is_empty());
+}
+
+#[test]
+fn test_edits_for_target_impossible() {
+    // A digraph never decodes to two equal letters.
+    let cipher = PlayfairCipher::new("playfair example");
+    assert_eq!(edits_for_target(&cipher, "bm", "ee"), Err(EditsNotFound));
+}
+
+#[test]
+fn test_pad_variants_doubled_letters() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let variants = pad_variants(&cipher, "balloon");
+    assert!(variants.len() > 1);
+    assert_eq!(variants[0].0, 'a');
+    for (pad, ciphertext) in &variants {
+        assert_eq!(
+            &cipher.clone().with_pad(*pad).encode("balloon").unwrap(),
+            ciphertext
+        );
+    }
+}
+
+#[test]
+fn test_pad_variants_without_fillers() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let variants = pad_variants(&cipher, "hide");
+    assert_eq!(variants, vec![('a', cipher.encode("hide").unwrap())]);
+}
+
+#[test]
+fn test_adjacency_neighbour_count() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let edges = adjacency(&cipher);
+    assert_eq!(edges.len(), 50);
+    for letter in ('a'..='z').filter(|&letter| letter != 'j') {
+        let neighbours = edges
+            .iter()
+            .filter(|&&(a, b)| a == letter || b == letter)
+            .count();
+        assert_eq!(neighbours, 4, "letter {}", letter);
+    }
+    assert!(edges.contains(&('p', 'l')));
+    assert!(edges.contains(&('p', 'i')));
+    assert!(edges.contains(&('f', 'p')));
+}
+
+#[test]
+fn test_case_distribution_wikipedia_example() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let text = "hide the gold in the tree stump";
+    let (same_row, same_column, rectangle) = case_distribution(&cipher, text);
+    assert_eq!(
+        same_row + same_column + rectangle,
+        cipher.digraphs(text).count()
+    );
+    // "ex" and "tu" are in the same row, "de" shares a column.
+    assert_eq!((same_row, same_column, rectangle), (2, 1, 10));
+}
+
+#[test]
+fn test_reachable_outputs() {
+    let outputs = reachable_outputs(&PlayfairCipher::new(""));
+    assert_eq!(outputs.len(), 25);
+    assert!(!outputs.contains(&'j'));
+    assert_eq!(reachable_outputs(&PlayfairCipher::new("jump")), outputs);
+}
+
+#[test]
+fn test_crib_map() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let map = crib_map(&cipher, &["hide", "gold", "tree"]);
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["hide"], "bmod");
+    assert_eq!(map["tree"], cipher.encode("tree").unwrap());
+    let ciphertext = cipher.encode("hide the gold").unwrap();
+    assert!(ciphertext.starts_with(&map["hide"]));
+}
+
+#[test]
+fn test_crack_difficulty() {
+    assert_eq!(crack_difficulty(0), Difficulty::Hard);
+    assert_eq!(crack_difficulty(99), Difficulty::Hard);
+    assert_eq!(crack_difficulty(100), Difficulty::Medium);
+    assert_eq!(crack_difficulty(299), Difficulty::Medium);
+    assert_eq!(crack_difficulty(300), Difficulty::Easy);
+    assert_eq!(crack_difficulty(10_000), Difficulty::Easy);
+}
+
+#[test]
+fn test_distinct_output_digraphs() {
+    let cipher = PlayfairCipher::new("playfair example");
+    assert_eq!(distinct_output_digraphs(&cipher), 600);
+}
+
+#[test]
+fn test_ij_equivalent_keys() {
+    let cipher = PlayfairCipher::new("jigsaw puzzle");
+    let keys = ij_equivalent_keys(&cipher);
+    assert_eq!(keys.len(), 2);
+    assert_ne!(keys[0], keys[1]);
+    for key in &keys {
+        let equivalent = PlayfairCipher::from_grid(key).unwrap();
+        assert_eq!(equivalent.grid(), cipher.grid());
+        assert_eq!(
+            equivalent.encode("jim").unwrap(),
+            cipher.encode("jim").unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_letter_histogram() {
+    let counts = letter_histogram("Hello, World! Jj");
+    assert_eq!(counts.iter().sum::<usize>(), 12);
+    assert_eq!(counts[(b'l' - b'a') as usize], 3);
+    assert_eq!(counts[(b'o' - b'a') as usize], 2);
+    assert_eq!(counts[(b'h' - b'a') as usize], 1);
+    assert_eq!(counts[(b'j' - b'a') as usize], 2);
+    assert_eq!(counts[(b'i' - b'a') as usize], 0);
+}
+
+#[test]
+fn test_all_unpaddings() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let b = cipher.encode("tree").unwrap();
+    assert_eq!(
+        all_unpaddings(&cipher, &b),
+        ["trexex", "treex", "trexe", "tree"]
+    );
+
+    let b = cipher.encode("fox taxi").unwrap();
+    assert_eq!(
+        all_unpaddings(&cipher, &b),
+        ["fox taxix", "fox taxi", "fox tai"]
+    );
+
+    // a doubled pad is split and completed with 'q'
+    let b = cipher.encode("xx").unwrap();
+    assert_eq!(all_unpaddings(&cipher, &b), ["xqxq", "xxq", "xqx", "xx"]);
+
+    let cipher = cipher.with_pads(&['z', 'q']);
+    let b = cipher.encode("tree").unwrap();
+    assert_eq!(
+        all_unpaddings(&cipher, &b),
+        ["treqez", "treez", "treqe", "tree"]
+    );
+}
+
+#[test]
+fn test_digraph_counts() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let counts = digraph_counts(&cipher, "hide hide the");
+    assert_eq!(counts[&('b', 'm')], 2);
+    assert_eq!(counts[&('o', 'd')], 2);
+    assert_eq!(counts.values().sum::<usize>(), 6);
+}
+
+#[test]
+fn test_digraph_entropy() {
+    let cipher = PlayfairCipher::new("playfair example");
+    assert_eq!(digraph_entropy(&cipher, ""), 0.0);
+    assert_eq!(digraph_entropy(&cipher, "aaaaaaaa"), 0.0);
+    // six distinct digraphs, each occurring once
+    let entropy = digraph_entropy(&cipher, "hide the gold");
+    assert!((entropy - 6f64.log2()).abs() < 1e-9);
+    let repetitive = digraph_entropy(&cipher, "abababababababababab");
+    let varied = digraph_entropy(&cipher, "the quick brown fox jumps over the lazy dog");
+    assert!(repetitive < varied);
+}
+
+#[test]
+fn test_grids_equal() {
+    assert!(grids_equal("hello", "helo"));
+    assert!(grids_equal("hello world", "helowrd"));
+    assert!(grids_equal("jam", "iam"));
+    assert!(grids_equal("abc", ""));
+    assert!(!grids_equal("hello", "olleh"));
+    assert!(!grids_equal("playfair example", "playfair"));
+}
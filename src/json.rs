@@ -0,0 +1,223 @@
+use std::fmt;
+use std::string::FromUtf8Error;
+
+use crate::PlayfairCipher;
+
+/// Error returned by [`PlayfairCipher::encode_json_values`] and
+/// [`PlayfairCipher::decode_json_values`].
+#[derive(Debug)]
+pub enum JsonError {
+    /// The string starting at this byte offset has no closing quote.
+    UnterminatedString(usize),
+    /// The escape sequence at this byte offset is malformed.
+    InvalidEscape(usize),
+    /// The transformed value is not valid UTF-8.
+    Utf8(FromUtf8Error),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonError::UnterminatedString(offset) => {
+                write!(f, "unterminated string at offset {}", offset)
+            }
+            JsonError::InvalidEscape(offset) => {
+                write!(f, "invalid escape sequence at offset {}", offset)
+            }
+            JsonError::Utf8(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JsonError::Utf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<FromUtf8Error> for JsonError {
+    fn from(err: FromUtf8Error) -> Self {
+        JsonError::Utf8(err)
+    }
+}
+
+impl PlayfairCipher {
+    /// Encodes the string values of the JSON document `json`, leaving object
+    /// keys, numbers, literals and the layout untouched.
+    ///
+    /// Only string literals are parsed; the rest of the document is copied as
+    /// it is without being validated. Values are unescaped before encoding and
+    /// escaped again afterwards, so escape sequences may be written differently
+    /// in the result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::new("playfair example");
+    /// let json = r#"{"note": "hide the gold in the tree stump", "count": 3}"#;
+    /// let encoded = cipher.encode_json_values(json).unwrap();
+    /// assert_eq!(encoded, r#"{"note": "bmod zbx dnab ek udm uixmm ouvif", "count": 3}"#);
+    /// ```
+    pub fn encode_json_values(&self, json: &str) -> Result<String, JsonError> {
+        map_json_values(json, |value| self.encode(value))
+    }
+
+    /// Decodes the string values of the JSON document `json`, the inverse of
+    /// `encode_json_values`.
+    pub fn decode_json_values(&self, json: &str) -> Result<String, JsonError> {
+        map_json_values(json, |value| self.decode(value))
+    }
+}
+
+fn map_json_values(
+    json: &str,
+    mut transform: impl FnMut(&str) -> Result<String, FromUtf8Error>,
+) -> Result<String, JsonError> {
+    let mut result = String::with_capacity(json.len() + 16);
+    let mut rest = json;
+    while let Some(start) = rest.find('"') {
+        result.push_str(&rest[..start]);
+        let offset = json.len() - rest.len() + start;
+        let len =
+            string_literal_len(&rest[start..]).ok_or(JsonError::UnterminatedString(offset))?;
+        let literal = &rest[start..start + len];
+        rest = &rest[start + len..];
+        if rest.trim_start().starts_with(':') {
+            // object key
+            result.push_str(literal);
+        } else {
+            let value = unescape(&literal[1..len - 1], offset + 1)?;
+            escape_into(&transform(&value)?, &mut result);
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+// Returns the length in bytes of the string literal at the start of `text`,
+// including both quotes.
+fn string_literal_len(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.bytes().enumerate().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == b'\\' {
+            escaped = true;
+        } else if c == b'"' {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+// Resolves the escape sequences of the contents of a string literal which
+// starts at byte `offset` of the document.
+fn unescape(text: &str, offset: usize) -> Result<String, JsonError> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let invalid = JsonError::InvalidEscape(offset + i);
+        let unescaped = match chars.next().map(|(_, c)| c) {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = hex4(&mut chars).ok_or(invalid)?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    // a surrogate pair encodes a character outside the BMP
+                    let low = match (chars.next(), chars.next()) {
+                        (Some((_, '\\')), Some((_, 'u'))) => hex4(&mut chars),
+                        _ => None,
+                    };
+                    match low {
+                        Some(low) if (0xdc00..0xe000).contains(&low) => {
+                            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                        }
+                        _ => return Err(JsonError::InvalidEscape(offset + i)),
+                    }
+                } else {
+                    high
+                };
+                char::from_u32(code).ok_or(JsonError::InvalidEscape(offset + i))?
+            }
+            _ => return Err(invalid),
+        };
+        result.push(unescaped);
+    }
+    Ok(result)
+}
+
+fn hex4(chars: &mut std::str::CharIndices) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..4 {
+        code = code * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    Some(code)
+}
+
+// Appends `text` to `result` as a JSON string literal.
+fn escape_into(text: &str, result: &mut String) {
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+}
+
+#[test]
+fn test_json_values_roundtrip() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let json = r#"{
+        "name": "the tree",
+        "tags": ["gold", "stump\né"],
+        "nested": {"key": "hide", "n": -1.5e3, "ok": true, "none": null}
+    }"#;
+    let encoded = cipher.encode_json_values(json).unwrap();
+    assert!(encoded.contains(r#""name": "#));
+    assert!(encoded.contains(r#""key": "bmod""#));
+    assert!(encoded.contains("-1.5e3"));
+    assert!(!encoded.contains("the tree"));
+    let decoded = cipher.decode_json_values(&encoded).unwrap();
+    assert_eq!(
+        decoded,
+        r#"{
+        "name": "the treex",
+        "tags": ["gold", "stump\néx"],
+        "nested": {"key": "hide", "n": -1.5e3, "ok": true, "none": null}
+    }"#
+    );
+}
+
+#[test]
+fn test_json_values_errors() {
+    let cipher = PlayfairCipher::new("playfair example");
+    assert!(matches!(
+        cipher.encode_json_values(r#"{"a": "open"#),
+        Err(JsonError::UnterminatedString(6))
+    ));
+    assert!(matches!(
+        cipher.encode_json_values(r#"{"a": "bad \q"}"#),
+        Err(JsonError::InvalidEscape(11))
+    ));
+}
@@ -1,5 +1,16 @@
+use std::fmt;
+use std::ops::Range;
 use std::string::FromUtf8Error;
 
+pub mod analysis;
+mod json;
+mod multi_key;
+mod record;
+
+pub use json::JsonError;
+pub use multi_key::MultiKeyDecoder;
+pub use record::RecordError;
+
 /// Data structure for fast Playfair encoding and decoding of text.
 ///
 /// # Example
@@ -12,6 +23,7 @@ use std::string::FromUtf8Error;
 /// let c = cipher.decode(&b).unwrap();
 /// assert_eq!(a, c);
 /// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PlayfairCipher {
     // Maps a letter index (0 to 24 inclusively) to a position which is encoded
     // as row*8 + col, where row and col are numbers from 1 to 5 inclusively.
@@ -21,11 +33,20 @@ pub struct PlayfairCipher {
     // If an index falls outside the range from 1 to 5 inclusively, then 0 will
     // be mapped to 5 and 6 to 1 (wrap around).
     letters: [u8; 64],
+    // Letter index of the filler used to split double letters and to complete
+    // a trailing single letter.
+    pad: u8,
+    // Letter indices of the fillers to alternate between by digraph position,
+    // or empty to always use `pad`.
+    pads: Vec<u8>,
+    // Direction in which encoding shifts letters of the same row or column.
+    shift: ShiftDirection,
 }
 
 impl PlayfairCipher {
     const X_INDEX: u8 = b'x' - b'a' - 1;
     const IJ_INDEX: u8 = b'i' - b'a';
+    const Q_INDEX: u8 = b'q' - b'a' - 1;
 
     pub fn print(&self) {
         let mut s = String::new();
@@ -38,30 +59,145 @@ impl PlayfairCipher {
         println!("{}", s);
     }
 
+    /// Returns the 0-based `(row, col)` of `letter` in the square, or `None`
+    /// if it is not a lowercase letter. 'j' is located at the cell of 'i'.
+    pub fn position(&self, letter: char) -> Option<(usize, usize)> {
+        if !letter.is_ascii() {
+            return None;
+        }
+        let pos = self.positions[Self::letter_index(letter as u8)? as usize] as usize;
+        Some((pos / 8 - 1, pos % 8 - 1))
+    }
+
+    /// Returns whether `letter` lies in the first or last row or column of the
+    /// square, where shifting along a row or column wraps around. Characters
+    /// which are not lowercase letters are never on the border.
+    pub fn is_border(&self, letter: char) -> bool {
+        match self.position(letter) {
+            Some((row, col)) => row == 0 || row == 4 || col == 0 || col == 4,
+            None => false,
+        }
+    }
+
+    /// Returns the letter index (0 to 24 inclusively, with 'j' folded into
+    /// 'i') at each cell of the square in row-major order. This is the inverse
+    /// of [`position`](Self::position).
+    pub fn inverse_positions(&self) -> [u8; 25] {
+        let mut inverse = [0u8; 25];
+        for (cell, letter) in inverse.iter_mut().zip(self.square()) {
+            *cell = Self::letter_index(letter).unwrap();
+        }
+        inverse
+    }
+
+    // Returns the letters of the square in row-major order.
+    pub(crate) fn square(&self) -> [u8; 25] {
+        let mut square = [0u8; 25];
+        for (i, letter) in square.iter_mut().enumerate() {
+            *letter = self.letters[(i / 5 + 1) * 8 + i % 5 + 1];
+        }
+        square
+    }
+
+    // Returns a cipher with the same settings whose square holds `square` in
+    // row-major order.
+    pub(crate) fn with_square(&self, square: &[u8; 25]) -> Self {
+        let key: String = square.iter().map(|&letter| letter as char).collect();
+        let cipher = Self::new(&key);
+        Self {
+            positions: cipher.positions,
+            letters: cipher.letters,
+            ..self.clone()
+        }
+    }
+
+    // Maps a lowercase letter to its letter index (0 to 24 inclusively), folding
+    // 'j' into 'i'.
+    pub(crate) fn letter_index(letter: u8) -> Option<u8> {
+        let index = letter.wrapping_sub(b'a');
+        if index >= 26 {
+            None
+        } else if index <= Self::IJ_INDEX {
+            Some(index)
+        } else {
+            Some(index - 1)
+        }
+    }
+
+    // Maps a letter index (0 to 24 inclusively) back to its lowercase letter.
+    pub(crate) fn index_letter(index: u8) -> u8 {
+        if index <= Self::IJ_INDEX {
+            index + b'a'
+        } else {
+            index + b'a' + 1
+        }
+    }
+
     pub fn new(key: &str) -> Self {
+        Self::new_with_fill_iter(key, std::iter::empty())
+    }
+
+    /// Like `new`, but also returns the characters of `key` which were
+    /// skipped, either because they are no lowercase letters or because the
+    /// letter was already placed ('i' and 'j' count as the same letter).
+    pub fn new_verbose(key: &str) -> (Self, Vec<char>) {
+        let mut skipped = Vec::new();
+        let cipher = Self::new_reporting_skipped(key, std::iter::empty(), |c| skipped.push(c));
+        (cipher, skipped)
+    }
+
+    /// Like `new`, but fills the cells left after `key` with the letters of
+    /// `fill` in order instead of alphabetically, e.g. to use a frequency
+    /// ordered alphabet. Letters already placed and characters other than
+    /// lowercase letters are skipped. Any letters `fill` leaves out follow in
+    /// alphabetical order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::new_with_fill_iter("playfair", ('a'..='z').rev());
+    /// assert_eq!(cipher.grid(), "playfirzxwvutsqonmkhgedcb");
+    /// ```
+    pub fn new_with_fill_iter(key: &str, fill: impl Iterator<Item = char>) -> Self {
+        Self::new_reporting_skipped(key, fill, |_| {})
+    }
+
+    // Builds the cipher like `new_with_fill_iter` and calls `skipped` with
+    // each character of `key` which is not placed into the square.
+    fn new_reporting_skipped(
+        key: &str,
+        fill: impl Iterator<Item = char>,
+        mut skipped: impl FnMut(char),
+    ) -> Self {
         let mut positions = [255u8; 25];
         let mut letters = [0u8; 64];
         let mut pos = (1, 1);
-        // fill square with characters from `key`
-        for &letter in key.as_bytes() {
-            let letter_index = if letter < b'j' {
-                letter.wrapping_sub(b'a')
-            } else {
-                letter.wrapping_sub(b'a' + 1)
-            } as usize;
-            if letter_index >= 25 {
-                // ignore characters which are non-alphabetical or non-lowercase
-                continue;
-            }
+        // fill square with characters from `key`, then from `fill`
+        let key = key.chars().map(|c| (c, true));
+        for (c, from_key) in key.chain(fill.map(|c| (c, false))) {
+            let letter_index = match u8::try_from(c).ok().and_then(Self::letter_index) {
+                Some(letter_index) => letter_index as usize,
+                None => {
+                    // ignore characters which are non-alphabetical or non-lowercase
+                    if from_key {
+                        skipped(c);
+                    }
+                    continue;
+                }
+            };
             if positions[letter_index] != 255u8 {
                 // Already taken?
+                if from_key {
+                    skipped(c);
+                }
                 continue;
             }
 
             // update `positions` and `letters`
             let encoded_pos = pos.0 * 8 + pos.1;
             positions[letter_index] = encoded_pos;
-            letters[encoded_pos as usize] = if letter == b'j' { b'i' } else { letter };
+            letters[encoded_pos as usize] = Self::index_letter(letter_index as u8);
 
             // Go to next valid `pos`
             pos.1 += 1;
@@ -103,21 +239,805 @@ impl PlayfairCipher {
             letters[col + 6 * 8] = letters[col + 8];
         }
 
-        Self { positions, letters }
+        Self {
+            positions,
+            letters,
+            pad: Self::X_INDEX,
+            pads: Vec::new(),
+            shift: ShiftDirection::RightDown,
+        }
+    }
+
+    /// Builds the cipher keyed by a list of words, e.g. a mnemonic phrase.
+    /// Uppercase letters count as lowercase, everything but letters is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::from_mnemonic(&["Gravity", "Falls"]);
+    /// assert_eq!(cipher.encode("attack at dawn").unwrap(), "gffgbm gf nfaw");
+    /// ```
+    pub fn from_mnemonic(words: &[&str]) -> Self {
+        Self::new(&Self::normalize_key(&words.concat()))
+    }
+
+    // Lowercases the ASCII letters of `key` and drops all other characters.
+    pub(crate) fn normalize_key(key: &str) -> String {
+        key.chars()
+            .filter(char::is_ascii_alphabetic)
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+
+    /// Builds the cipher whose square is given by `grid`, 25 letters in
+    /// row-major order. Whitespace is ignored and 'j' stands for 'i'.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::from_grid("playf irexm bcdgh knoqs tuvwz").unwrap();
+    /// assert_eq!(cipher.encode("hide").unwrap(), "bmod");
+    /// ```
+    pub fn from_grid(grid: &str) -> Result<Self, GridError> {
+        Ok(Self::new(&Self::validate_grid(grid.chars(), false)?))
+    }
+
+    // Checks that `grid` consists of 25 distinct letters, ignoring whitespace,
+    // and returns them. With `lowercase_first`, uppercase letters are accepted
+    // and lowercased.
+    fn validate_grid(
+        grid: impl Iterator<Item = char>,
+        lowercase_first: bool,
+    ) -> Result<String, GridError> {
+        let mut seen = [false; 25];
+        let mut letters = String::with_capacity(25);
+        for c in grid.filter(|c| !c.is_whitespace()) {
+            let letter = if lowercase_first {
+                c.to_ascii_lowercase()
+            } else {
+                c
+            };
+            let index = u8::try_from(letter).ok().and_then(Self::letter_index);
+            let index = index.ok_or(GridError::InvalidChar(c))? as usize;
+            if seen[index] {
+                return Err(GridError::Duplicate(letter));
+            }
+            seen[index] = true;
+            letters.push(letter);
+        }
+        if letters.len() != 25 {
+            return Err(GridError::WrongLength(letters.len()));
+        }
+        Ok(letters)
+    }
+
+    /// Builds the cipher from the letters of a square read in row-major order,
+    /// e.g. by OCR from a printed key square.
+    ///
+    /// Unlike `from_grid`, uppercase letters are accepted, and a square with a
+    /// single letter left out is reported as [`GridError::Missing`] naming the
+    /// letter, so that it can be fixed by hand. Whitespace is ignored and 'j'
+    /// stands for 'i'.
+    pub fn from_ocr_letters(letters: &[char]) -> Result<Self, GridError> {
+        match Self::validate_grid(letters.iter().copied(), true) {
+            Ok(grid) => Ok(Self::new(&grid)),
+            Err(GridError::WrongLength(24)) => {
+                // the 24 letters are valid and distinct, so one is missing
+                let present: Vec<u8> = letters
+                    .iter()
+                    .filter_map(|c| u8::try_from(c.to_ascii_lowercase()).ok())
+                    .filter_map(Self::letter_index)
+                    .collect();
+                let missing = (0..25).find(|index| !present.contains(index)).unwrap();
+                Err(GridError::Missing(Self::index_letter(missing) as char))
+            }
+            Err(err) => Err(err),
+        }
     }
 
+    /// Returns the filler letter.
+    pub fn pad(&self) -> char {
+        Self::index_letter(self.pad) as char
+    }
+
+    /// Sets the direction in which encoding shifts letters of the same row or
+    /// column; decoding shifts the opposite way.
+    pub fn with_shift_direction(mut self, shift: ShiftDirection) -> Self {
+        self.shift = shift;
+        self
+    }
+
+    /// Returns the 25 letters of the square in row-major order, suitable for
+    /// `from_grid`.
+    pub fn grid(&self) -> String {
+        self.square().iter().map(|&letter| letter as char).collect()
+    }
+
+    /// Returns whether the square is the plain alphabetical one, as produced by
+    /// an empty key or a key whose distinct letters are the first letters of
+    /// the alphabet, in order (e.g. "abc" or "aabc", but not "abd").
+    pub fn is_identity(&self) -> bool {
+        self.square()
+            .iter()
+            .enumerate()
+            .all(|(i, &letter)| letter == Self::index_letter(i as u8))
+    }
+
+    /// Returns a fingerprint of the square and all other settings which is
+    /// stable across runs and platforms.
+    ///
+    /// Settings which behave identically share a fingerprint:
+    ///
+    /// - squares which only differ by a cyclic shift of rows or columns,
+    /// - a square rotated by 180° with the opposite shift direction, since
+    ///   this swaps left with right and up with down,
+    /// - alternating pads (see `with_pads`) which repeat a shorter list, and
+    ///   a list of a single repeated pad, which is the same as no list.
+    pub fn config_fingerprint(&self) -> u64 {
+        let mut square = self.square();
+        let mut shift = self.shift;
+        if shift == ShiftDirection::LeftUp {
+            square.reverse();
+            shift = ShiftDirection::RightDown;
+        }
+        // move 'a' to the top left corner to undo cyclic shifts
+        let a = square.iter().position(|&letter| letter == b'a').unwrap();
+        let (row, col) = (a / 5, a % 5);
+        let mut bytes = Vec::with_capacity(27 + self.pads.len());
+        for i in 0..25 {
+            bytes.push(square[(i / 5 + row) % 5 * 5 + (i % 5 + col) % 5]);
+        }
+        bytes.push(self.pad);
+        bytes.push(shift as u8);
+        // `with_pads` already reduced the pads to their period
+        bytes.extend(&self.pads);
+
+        // 64-bit FNV-1a
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+
+    /// Captures everything needed to reproduce the encoding of `text`.
+    pub fn reproduction_recipe(&self, text: &str) -> Recipe {
+        Recipe {
+            grid: self.grid(),
+            pad: self.pad(),
+            pads: self
+                .pads
+                .iter()
+                .map(|&pad| Self::index_letter(pad) as char)
+                .collect(),
+            shift: self.shift,
+            plaintext: text.to_string(),
+        }
+    }
+
+    /// Uses `pad` instead of 'x' as the filler letter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pad` is not a lowercase ASCII letter.
+    pub fn with_pad(mut self, pad: char) -> Self {
+        assert!(
+            pad.is_ascii_lowercase(),
+            "pad must be a lowercase ASCII letter"
+        );
+        self.pad = Self::letter_index(pad as u8).unwrap();
+        self.pads.clear();
+        self
+    }
+
+    /// Alternates between the filler letters `pads` to flatten their
+    /// frequency: the filler of the n-th digraph is `pads[n % pads.len()]`.
+    /// The first of `pads` becomes the pad returned by `pad`, and a later
+    /// `with_pad` goes back to a single filler.
+    ///
+    /// Since the fillers no longer share one letter, `encode_with_fillers`
+    /// and `decode_unpadded_exact` are the way to remove them again.
+    ///
+    /// Only the shortest list which repeats to `pads` is kept, e.g. "zqzq"
+    /// is kept as "zq", and a single repeated letter just sets the pad.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pads` is empty or contains a character which is not a
+    /// lowercase ASCII letter.
+    pub fn with_pads(mut self, pads: &[char]) -> Self {
+        assert!(
+            !pads.is_empty() && pads.iter().all(char::is_ascii_lowercase),
+            "pads must be lowercase ASCII letters"
+        );
+        let mut pads: Vec<u8> = pads
+            .iter()
+            .map(|&pad| Self::letter_index(pad as u8).unwrap())
+            .collect();
+        let period = (1..=pads.len())
+            .find(|&period| {
+                pads.len().is_multiple_of(period)
+                    && (period..pads.len()).all(|i| pads[i] == pads[i - period])
+            })
+            .unwrap();
+        self.pad = pads[0];
+        pads.truncate(if period > 1 { period } else { 0 });
+        self.pads = pads;
+        self
+    }
+
+    /// Splits the lowercase letters of `text` into the digraphs that `encode`
+    /// enciphers, with the pad inserted between double letters and after a
+    /// trailing single letter. Other characters are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::new("");
+    /// let pairs: Vec<_> = cipher.digraphs("Balloon!").collect();
+    /// assert_eq!(pairs, [('a', 'l'), ('l', 'o'), ('o', 'n')]);
+    /// ```
+    pub fn digraphs<'a>(&'a self, text: &'a str) -> Digraphs<'a> {
+        Digraphs {
+            bytes: text.bytes(),
+            pending: None,
+            pad: self.pad,
+            pads: &self.pads,
+            count: 0,
+        }
+    }
+
+    /// Encodes the lowercase letters of `text`, keeping all other characters.
+    ///
+    /// Letters are enciphered in pairs. Two equal letters are split by
+    /// inserting the pad ('x' unless set with `with_pad`) after the first one,
+    /// and a trailing single letter is completed with the pad. A run of n equal
+    /// letters therefore becomes n pairs: "aaa" decodes to "axaxax". A pad
+    /// which has to be split or completed itself is followed by 'q' instead
+    /// (by 'x' if 'q' is the pad), so "xx" decodes to "xqxq".
     pub fn encode(&self, text: &str) -> Result<String, FromUtf8Error> {
         self.encode_or_decode(text, true)
     }
 
+    /// Encodes the digraphs of `text` (see `digraphs`) one by one, calling `f`
+    /// with each plaintext digraph and its ciphertext digraph. Nothing is
+    /// collected, and characters other than lowercase letters are skipped.
+    pub fn encode_each<F: FnMut((char, char), (char, char))>(&self, text: &str, mut f: F) {
+        let mut stats = EncodeStats::default();
+        for (a, b) in self.digraphs(text) {
+            let (c, d) = self.encode_or_decode_pair(
+                Self::letter_index(a as u8).unwrap(),
+                Self::letter_index(b as u8).unwrap(),
+                true,
+                &mut stats,
+            );
+            f((a, b), (c as char, d as char));
+        }
+    }
+
+    /// Like `encode`, but every run of characters which are not ASCII letters
+    /// is replaced by a single space. Uppercase letters are kept unencoded, as
+    /// with `encode`.
+    ///
+    /// This is lossy: decoding yields the single spaces, not the original
+    /// runs, so punctuation, digits and line breaks are lost.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::new("gravity falls");
+    /// let b = cipher.encode_compact("attack -- at dawn!!!").unwrap();
+    /// assert_eq!(b, "gffgbm gf nfaw ");
+    /// assert_eq!(cipher.decode(&b).unwrap(), "attack at dawn ");
+    /// ```
+    pub fn encode_compact(&self, text: &str) -> Result<String, FromUtf8Error> {
+        let mut compact = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c.is_ascii_alphabetic() {
+                compact.push(c);
+            } else if !compact.ends_with(' ') {
+                compact.push(' ');
+            }
+        }
+        self.encode(&compact)
+    }
+
+    /// Like `encode`, but every 'ß' is written as "ss" first, so that it is
+    /// enciphered instead of being copied into the ciphertext. Use
+    /// `decode_sharp_s` to turn it back.
+    pub fn encode_sharp_s(&self, text: &str) -> Result<String, FromUtf8Error> {
+        self.encode(&text.replace('ß', "ss"))
+    }
+
+    /// Like `decode`, but turns the letters an 'ß' was expanded to by
+    /// `encode_sharp_s` back into 'ß'.
+    ///
+    /// This is a heuristic: two adjacent 's', or two 's' around the filler
+    /// which split them, become 'ß' whether they came from one or not. So
+    /// "wasser" decodes to "waßerx", with the trailing pad kept as `decode`
+    /// keeps it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::new("playfair example");
+    /// let b = cipher.encode_sharp_s("die straße").unwrap();
+    /// assert!(!b.contains('ß'));
+    /// assert_eq!(cipher.decode_sharp_s(&b).unwrap(), "die straße");
+    /// ```
+    pub fn decode_sharp_s(&self, text: &str) -> Result<String, FromUtf8Error> {
+        let decoded = self.decode(text)?.into_bytes();
+        let s = Self::letter_index(b's').unwrap();
+        // letters with their byte offsets in `decoded`
+        let letters: Vec<(usize, u8)> = decoded
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, &c)| Some((offset, Self::letter_index(c)?)))
+            .collect();
+        // runs `(first letter, letter count)` to be replaced by 'ß'
+        let mut runs = Vec::new();
+        let mut k = 0;
+        while k + 1 < letters.len() {
+            let is_s = |i: usize| letters.get(i).map(|&(_, letter)| letter) == Some(s);
+            let adjacent = |i: usize| letters[i].0 + 1 == letters[i + 1].0;
+            let len = if k % 2 == 1 && is_s(k) && is_s(k + 1) && adjacent(k) {
+                2
+            } else if k % 2 == 0
+                && is_s(k)
+                && is_s(k + 2)
+                && letters[k + 1].1 == Self::filler_at(self.pad, &self.pads, k / 2, s)
+                && adjacent(k)
+                && adjacent(k + 1)
+            {
+                3
+            } else {
+                1
+            };
+            if len > 1 {
+                runs.push((k, len));
+            }
+            k += len;
+        }
+        let mut result = Vec::with_capacity(decoded.len());
+        let mut start = 0;
+        for (k, len) in runs {
+            result.extend(&decoded[start..letters[k].0]);
+            result.extend("ß".as_bytes());
+            start = letters[k + len - 1].0 + 1;
+        }
+        result.extend(&decoded[start..]);
+        String::from_utf8(result)
+    }
+
+    /// Like `encode`, but fails without encoding if the ciphertext would have
+    /// more than `max_letters` letters (fillers included).
+    pub fn encode_within(&self, text: &str, max_letters: usize) -> Result<String, EncodeError> {
+        let letters = Self::letter_count(text) + self.padding_count(text);
+        if letters > max_letters {
+            return Err(EncodeError::TooLong {
+                letters,
+                max_letters,
+            });
+        }
+        Ok(self.encode(text)?)
+    }
+
+    /// Prepends fillers to `text` so that its ciphertext has `target_letters`
+    /// letters (or one less, since ciphertexts have an even number of
+    /// letters), encodes it and returns the ciphertext with the number of
+    /// prepended fillers, which `decode_padded` needs to remove them.
+    ///
+    /// The fillers are pairs of the pad and a second letter, encoded apart
+    /// from `text`, so they neither need splitting themselves nor change how
+    /// `text` splits into digraphs or which fillers it gets.
+    pub fn encode_padded_to(
+        &self,
+        text: &str,
+        target_letters: usize,
+    ) -> Result<(String, usize), EncodeError> {
+        let letters = Self::letter_count(text) + self.padding_count(text);
+        if letters > target_letters {
+            return Err(EncodeError::TooLong {
+                letters,
+                max_letters: target_letters,
+            });
+        }
+        let pairs = (target_letters - letters) / 2;
+        let filler = [
+            Self::index_letter(self.pad),
+            Self::index_letter(Self::filler_after(self.pad, self.pad)),
+        ];
+        let mut padded = self.encode(&String::from_utf8(filler.repeat(pairs)).unwrap())?;
+        padded.push_str(&self.encode(text)?);
+        Ok((padded, 2 * pairs))
+    }
+
+    /// Decodes `text` and removes the first `padding` letters, the fillers
+    /// prepended by `encode_padded_to`.
+    pub fn decode_padded(&self, text: &str, padding: usize) -> Result<String, FromUtf8Error> {
+        let decoded = self.decode(text)?;
+        let mut letters = 0;
+        Ok(decoded
+            .chars()
+            .filter(|c| {
+                if letters < padding && c.is_ascii_lowercase() {
+                    letters += 1;
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect())
+    }
+
+    /// Returns the number of pad letters `encode` inserts into `text`.
+    pub fn padding_count(&self, text: &str) -> usize {
+        self.digraphs(text).count() * 2 - Self::letter_count(text)
+    }
+
+    // Counts the letters of `text` which take part in encoding.
+    fn letter_count(text: &str) -> usize {
+        text.bytes()
+            .filter(|&c| Self::letter_index(c).is_some())
+            .count()
+    }
+
+    /// Encodes `text` under a square derived from this one for the period
+    /// `epoch` (e.g. a day number), so that the same text encodes differently
+    /// in different periods. Decoding requires the same `epoch`.
+    ///
+    /// The letters of the square are rotated along the reading order by one of
+    /// the 20 offsets from 1 to 24 which are not a multiple of 5, so epochs 20
+    /// apart share a square. Multiples of 5 are skipped since they merely
+    /// rotate whole rows, which leaves the cipher unchanged.
+    pub fn encode_timed(&self, text: &str, epoch: u64) -> Result<String, FromUtf8Error> {
+        self.rotated_for(epoch).encode(text)
+    }
+
+    pub fn decode_timed(&self, text: &str, epoch: u64) -> Result<String, FromUtf8Error> {
+        self.rotated_for(epoch).decode(text)
+    }
+
+    fn rotated_for(&self, epoch: u64) -> Self {
+        let step = (epoch % 20) as usize;
+        let mut square = self.square();
+        square.rotate_left(step + step / 4 + 1);
+        self.with_square(&square)
+    }
+
+    /// Encodes `text` with the square given by `grid`, see `from_grid`.
+    pub fn encode_with_grid(grid: &str, text: &str) -> Result<String, EncodeError> {
+        Ok(Self::from_grid(grid)?.encode(text)?)
+    }
+
+    /// Decodes `text` with the square given by `grid`, see `from_grid`.
+    pub fn decode_with_grid(grid: &str, text: &str) -> Result<String, EncodeError> {
+        Ok(Self::from_grid(grid)?.decode(text)?)
+    }
+
     pub fn decode(&self, text: &str) -> Result<String, FromUtf8Error> {
         self.encode_or_decode(text, false)
     }
 
+    /// Encodes each of `parts` on its own, so that the ciphertexts decode
+    /// back into the same parts with `decode_parts`. No pair spans two parts.
+    pub fn encode_parts(&self, parts: &[&str]) -> Result<Vec<String>, FromUtf8Error> {
+        parts.iter().map(|part| self.encode(part)).collect()
+    }
+
+    /// Decodes each of `parts` on its own, the inverse of `encode_parts`.
+    pub fn decode_parts(&self, parts: &[&str]) -> Result<Vec<String>, FromUtf8Error> {
+        parts.iter().map(|part| self.decode(part)).collect()
+    }
+
+    /// Decodes `text`, which should have an even number of letters.
+    ///
+    /// If the number of letters is odd, a letter was presumably lost. Then
+    /// the last letter is left out and the rest is decoded; the returned
+    /// [`RepairInfo`] reports the left out letter together with a repaired
+    /// decoding in which a letter was inserted where it was plausibly lost:
+    ///
+    /// - Before the first digraph of two equal letters, which encoding never
+    ///   produces, so the letter was lost before it. The inserted letter is
+    ///   a guess ('a', or 'b' if the digraph starts with 'a'), but the text
+    ///   after it decodes correctly again.
+    /// - Otherwise after the last letter, chosen so that the last digraph
+    ///   decodes to a letter completed with the pad, if there is such a
+    ///   letter.
+    pub fn decode_repair(&self, text: &str) -> Result<(String, RepairInfo), FromUtf8Error> {
+        if Self::letter_count(text).is_multiple_of(2) {
+            return Ok((self.decode(text)?, RepairInfo::default()));
+        }
+        let (offset, letter) = text
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| c.is_ascii_lowercase())
+            .unwrap();
+        let mut even = String::with_capacity(text.len());
+        even.push_str(&text[..offset]);
+        even.push_str(&text[offset + 1..]);
+        let (inserted_offset, inserted) = self.guess_lost_letter(text);
+        let mut repaired = String::with_capacity(text.len() + 1);
+        repaired.push_str(&text[..inserted_offset]);
+        repaired.push(inserted);
+        repaired.push_str(&text[inserted_offset..]);
+        let info = RepairInfo {
+            dropped: Some((offset, letter)),
+            inserted: Some((inserted_offset, inserted)),
+            repaired: Some(self.decode(&repaired)?),
+        };
+        Ok((self.decode(&even)?, info))
+    }
+
+    // Returns the byte offset at which a letter was plausibly lost from
+    // `text`, which has an odd number of letters, and a guess for the letter,
+    // see `decode_repair`.
+    fn guess_lost_letter(&self, text: &str) -> (usize, char) {
+        let letters: Vec<(usize, u8)> = text
+            .bytes()
+            .enumerate()
+            .filter_map(|(offset, c)| Some((offset, Self::letter_index(c)?)))
+            .collect();
+        let doubled = letters
+            .chunks(2)
+            .find(|pair| pair.len() == 2 && pair[0].1 == pair[1].1);
+        if let Some(pair) = doubled {
+            let letter = if pair[0].1 != 0 { 0 } else { 1 };
+            return (pair[0].0, Self::index_letter(letter) as char);
+        }
+        let (offset, last) = *letters.last().unwrap();
+        let mut stats = EncodeStats::default();
+        let pad = Self::index_letter(self.pad);
+        let letter = (0..25)
+            .filter(|&letter| letter != last)
+            .find(|&letter| {
+                let (_, second) = self.encode_or_decode_pair(last, letter, false, &mut stats);
+                second == pad
+            })
+            .unwrap_or(if last != 0 { 0 } else { 1 });
+        (offset + 1, Self::index_letter(letter) as char)
+    }
+
+    /// Maps each character of `decode(text)` to the part of `text` it was
+    /// decoded from, as pairs of byte ranges into the plaintext and into the
+    /// ciphertext.
+    ///
+    /// Both letters of a digraph map to the range from its first to its second
+    /// letter; an inserted pad maps to the letter it completes. All other
+    /// characters map to themselves.
+    pub fn decode_spans(&self, text: &str) -> Vec<(Range<usize>, Range<usize>)> {
+        let mut spans: Vec<(Range<usize>, Range<usize>)> = Vec::with_capacity(text.len() + 1);
+        let mut out = 0;
+        // index into `spans` and byte offset of the letter waiting for its partner
+        let mut pending: Option<(usize, usize)> = None;
+        for (i, c) in text.char_indices() {
+            let letter_index = if c.is_ascii() {
+                Self::letter_index(c as u8)
+            } else {
+                None
+            };
+            let letter_index = match letter_index {
+                Some(letter_index) => letter_index,
+                None => {
+                    spans.push((out..out + c.len_utf8(), i..i + c.len_utf8()));
+                    out += c.len_utf8();
+                    continue;
+                }
+            };
+            if let Some((k, start)) = pending {
+                if Self::letter_index(text.as_bytes()[start]) == Some(letter_index) {
+                    // the pad is inserted after the first of two equal letters
+                    spans.push((out..out + 1, start..start + 1));
+                    out += 1;
+                } else {
+                    spans[k].1 = start..i + 1;
+                    spans.push((out..out + 1, start..i + 1));
+                    out += 1;
+                    pending = None;
+                    continue;
+                }
+            }
+            pending = Some((spans.len(), i));
+            spans.push((out..out + 1, i..i + 1));
+            out += 1;
+        }
+        if let Some((_, start)) = pending {
+            spans.push((out..out + 1, start..start + 1));
+        }
+        spans
+    }
+
+    /// Decodes `text` into the letter indices (0 to 24 inclusively, with 'j'
+    /// folded into 'i') of the plaintext letters, dropping all other characters.
+    pub fn decode_indices(&self, text: &str) -> Vec<u8> {
+        self.encode_or_decode_bytes(text.as_bytes(), false, &mut EncodeStats::default())
+            .into_iter()
+            .filter_map(Self::letter_index)
+            .collect()
+    }
+
+    /// Checks the roundtrip for every combination of a few sample keys, each
+    /// pad letter as well as a few lists of alternating pads (see
+    /// `with_pads`), and both shift directions.
+    ///
+    /// Each sample text is encoded with `encode_with_fillers` and decoded with
+    /// `decode_unpadded_exact`, which has to give back the text with 'j'
+    /// replaced by 'i'. The samples include double letters, doubled pads and
+    /// texts with an odd number of letters.
+    pub fn conformance_report() -> ConformanceReport {
+        const KEYS: [&str; 4] = [
+            "",
+            "playfair example",
+            "Hello Playfair Cipher",
+            "zyxwvutsrqponmlkhgfedcba",
+        ];
+        const TEXTS: [&str; 5] = [
+            "",
+            "hide the gold in the tree stump",
+            "Attack at dawn!",
+            "aaa xx qq zz",
+            "jazz jig",
+        ];
+        const PAD_LISTS: [&[char]; 2] = [&['z', 'q'], &['q', 'x', 'a']];
+        let single_pads: Vec<(char, &'static [char])> = ('a'..='z')
+            .filter(|&pad| pad != 'j')
+            .map(|pad| (pad, &[][..]))
+            .collect();
+        let pad_lists = PAD_LISTS.iter().map(|&pads| (pads[0], pads));
+        let pad_settings: Vec<_> = single_pads.into_iter().chain(pad_lists).collect();
+        let mut report = ConformanceReport::default();
+        for key in KEYS {
+            for &(pad, pads) in &pad_settings {
+                for shift in [ShiftDirection::RightDown, ShiftDirection::LeftUp] {
+                    let mut cipher = Self::new(key).with_pad(pad).with_shift_direction(shift);
+                    if !pads.is_empty() {
+                        cipher = cipher.with_pads(pads);
+                    }
+                    let passed = TEXTS.iter().all(|text| {
+                        let roundtrip =
+                            cipher
+                                .encode_with_fillers(text)
+                                .and_then(|(encoded, mask)| {
+                                    cipher.decode_unpadded_exact(&encoded, &mask)
+                                });
+                        roundtrip.ok() == Some(text.replace('j', "i"))
+                    });
+                    report.cases.push(ConformanceCase {
+                        key,
+                        pad,
+                        pads,
+                        shift,
+                        passed,
+                    });
+                }
+            }
+        }
+        report
+    }
+
+    /// Returns the substitution tables of all 625 digraphs for encoding and
+    /// for decoding, in this order.
+    ///
+    /// The digraph of the letter indices `a` and `b` (0 to 24 inclusively,
+    /// with 'j' folded into 'i') is mapped to the letter indices at
+    /// `2 * (25 * a + b)` and `2 * (25 * a + b) + 1`. A digraph of two equal
+    /// letters is mapped as if its second letter were the pad, except that a
+    /// doubled pad is completed with 'q' ('x' if 'q' is the pad).
+    pub fn encode_lookup_tables(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut stats = EncodeStats::default();
+        let mut forward = Vec::with_capacity(2 * 625);
+        let mut inverse = Vec::with_capacity(2 * 625);
+        for a in 0..25 {
+            for b in 0..25 {
+                for (table, is_encode) in [(&mut forward, true), (&mut inverse, false)] {
+                    let (c, d) = self.encode_or_decode_pair(a, b, is_encode, &mut stats);
+                    table.push(Self::letter_index(c).unwrap());
+                    table.push(Self::letter_index(d).unwrap());
+                }
+            }
+        }
+        (forward, inverse)
+    }
+
+    /// Like `encode`, but also returns a mask telling for each letter of the
+    /// ciphertext whether it was inserted as a filler.
+    ///
+    /// With the mask, `decode_unpadded_exact` removes exactly the fillers,
+    /// even if the plaintext contains the pad letter itself.
+    pub fn encode_with_fillers(&self, text: &str) -> Result<(String, Vec<bool>), FromUtf8Error> {
+        let letters: Vec<u8> = text.bytes().filter_map(Self::letter_index).collect();
+        let mut mask = Vec::with_capacity(letters.len() + 2);
+        let mut i = 0;
+        while i < letters.len() {
+            let first = letters[i];
+            i += 1;
+            mask.push(false);
+            if i < letters.len() && letters[i] != first {
+                i += 1;
+                mask.push(false);
+            } else {
+                mask.push(true);
+            }
+        }
+        Ok((self.encode(text)?, mask))
+    }
+
+    /// Decodes `text` and removes the letters marked as fillers in `mask`, as
+    /// returned by `encode_with_fillers`. The plaintext is recovered exactly,
+    /// except that 'j' comes back as 'i'.
+    pub fn decode_unpadded_exact(
+        &self,
+        text: &str,
+        mask: &[bool],
+    ) -> Result<String, FromUtf8Error> {
+        let decoded = self.decode(text)?;
+        let mut result = String::with_capacity(decoded.len());
+        let mut letters = 0;
+        for c in decoded.chars() {
+            if c.is_ascii_lowercase() {
+                letters += 1;
+                if mask.get(letters - 1) == Some(&true) {
+                    continue;
+                }
+            }
+            result.push(c);
+        }
+        Ok(result)
+    }
+
+    /// Like `encode`, but also reports statistics about the encoding.
+    pub fn encode_with_stats(&self, text: &str) -> Result<(String, EncodeStats), FromUtf8Error> {
+        let mut stats = EncodeStats::default();
+        let result = self.encode_or_decode_bytes(text.as_bytes(), true, &mut stats);
+        Ok((String::from_utf8(result)?, stats))
+    }
+
+    /// Encodes raw bytes, which need not be valid UTF-8. Bytes other than
+    /// lowercase letters are copied, and `policy` decides what happens if the
+    /// result is not valid UTF-8.
+    pub fn encode_bytes(&self, text: &[u8], policy: Utf8Policy) -> Result<String, FromUtf8Error> {
+        self.encode_or_decode_bytes_with(text, true, policy)
+    }
+
+    /// Decodes raw bytes, see `encode_bytes`.
+    pub fn decode_bytes(&self, text: &[u8], policy: Utf8Policy) -> Result<String, FromUtf8Error> {
+        self.encode_or_decode_bytes_with(text, false, policy)
+    }
+
+    fn encode_or_decode_bytes_with(
+        &self,
+        text: &[u8],
+        is_encode: bool,
+        policy: Utf8Policy,
+    ) -> Result<String, FromUtf8Error> {
+        let result = self.encode_or_decode_bytes(text, is_encode, &mut EncodeStats::default());
+        match policy {
+            Utf8Policy::Error => String::from_utf8(result),
+            Utf8Policy::Lossy => Ok(String::from_utf8_lossy(&result).into_owned()),
+        }
+    }
+
     pub fn encode_or_decode(&self, text: &str, is_encode: bool) -> Result<String, FromUtf8Error> {
+        let mut stats = EncodeStats::default();
+        String::from_utf8(self.encode_or_decode_bytes(text.as_bytes(), is_encode, &mut stats))
+    }
+
+    fn encode_or_decode_bytes(
+        &self,
+        text: &[u8],
+        is_encode: bool,
+        stats: &mut EncodeStats,
+    ) -> Vec<u8> {
         let mut result = Vec::<u8>::with_capacity(text.len() + 1);
         let mut last_pos = None;
-        for &c in text.as_bytes() {
+        let mut digraph = 0;
+        for &c in text {
             let letter_index = c.wrapping_sub(b'a');
             if letter_index >= 26 {
                 result.push(c);
@@ -130,12 +1050,16 @@ impl PlayfairCipher {
             };
             if let Some(pos) = last_pos {
                 if result[pos] == letter_index {
-                    // insert an 'x' to split double letter
-                    let (a, b) = self.encode_or_decode_pair(result[pos], Self::X_INDEX, is_encode);
+                    // insert the pad to split double letter
+                    let pad = Self::pad_at(self.pad, &self.pads, digraph);
+                    let (a, b) = self.encode_or_decode_pair(result[pos], pad, is_encode, stats);
+                    digraph += 1;
                     result[pos] = a;
                     result.push(b);
                 } else {
-                    let (a, b) = self.encode_or_decode_pair(result[pos], letter_index, is_encode);
+                    let (a, b) =
+                        self.encode_or_decode_pair(result[pos], letter_index, is_encode, stats);
+                    digraph += 1;
                     last_pos = None;
                     result[pos] = a;
                     result.push(b);
@@ -146,58 +1070,358 @@ impl PlayfairCipher {
             result.push(letter_index);
         }
         if let Some(pos) = last_pos {
-            let (a, b) = self.encode_or_decode_pair(result[pos], Self::X_INDEX, is_encode);
+            let pad = Self::pad_at(self.pad, &self.pads, digraph);
+            let (a, b) = self.encode_or_decode_pair(result[pos], pad, is_encode, stats);
             result[pos] = a;
             result.push(b);
         }
 
-        String::from_utf8(result)
+        result
+    }
+
+    // Returns the filler of the `digraph`-th digraph, see `with_pads`.
+    fn pad_at(pad: u8, pads: &[u8], digraph: usize) -> u8 {
+        if pads.is_empty() {
+            pad
+        } else {
+            pads[digraph % pads.len()]
+        }
+    }
+
+    // Returns the filler which completes the `digraph`-th digraph after
+    // `first`, the way `encode` chooses it.
+    pub(crate) fn filler_at(pad: u8, pads: &[u8], digraph: usize, first: u8) -> u8 {
+        let filler = Self::pad_at(pad, pads, digraph);
+        if first != filler {
+            filler
+        } else {
+            Self::filler_after(pad, first)
+        }
+    }
+
+    // Returns the filler which completes a pair starting with `letter`. The pad
+    // cannot complete a pair with itself, so it is followed by 'q' (or 'x' if
+    // 'q' is the pad).
+    fn filler_after(pad: u8, letter: u8) -> u8 {
+        if letter != pad {
+            pad
+        } else if pad != Self::Q_INDEX {
+            Self::Q_INDEX
+        } else {
+            Self::X_INDEX
+        }
     }
 
-    fn encode_or_decode_pair(&self, a: u8, b: u8, is_encode: bool) -> (u8, u8) {
+    // Tells which rule of the cipher applies to the pair of letter indices.
+    pub(crate) fn classify_pair(&self, a: u8, b: u8) -> PairCase {
         let pos_a = self.positions[a as usize];
         let pos_b = self.positions[b as usize];
         if pos_a == pos_b {
-            if a == Self::X_INDEX {
-                // Case not really defined in the Playfair Cipher description of Wikipedia. Let's improvise.
-                (a, b)
-            } else {
-                self.encode_or_decode_pair(a, Self::X_INDEX, is_encode)
-            }
+            PairCase::SameCell
         } else if (pos_a & 7) == (pos_b & 7) {
-            // same column
-            if is_encode {
-                (
-                    self.letters[(pos_a + 8) as usize],
-                    self.letters[(pos_b + 8) as usize],
-                )
-            } else {
-                (
-                    self.letters[(pos_a - 8) as usize],
-                    self.letters[(pos_b - 8) as usize],
-                )
-            }
+            PairCase::SameColumn
         } else if (pos_a & 0o70) == (pos_b & 0o70) {
-            // same row
-            if is_encode {
-                (
-                    self.letters[(pos_a + 1) as usize],
-                    self.letters[(pos_b + 1) as usize],
-                )
-            } else {
-                (
-                    self.letters[(pos_a - 1) as usize],
-                    self.letters[(pos_b - 1) as usize],
-                )
-            }
+            PairCase::SameRow
         } else {
-            let pos_c = (pos_a & 0o70) | (pos_b & 7);
-            let pos_d = (pos_b & 0o70) | (pos_a & 7);
-            (self.letters[pos_c as usize], self.letters[pos_d as usize])
+            PairCase::Rectangle
+        }
+    }
+
+    fn encode_or_decode_pair(
+        &self,
+        a: u8,
+        b: u8,
+        is_encode: bool,
+        stats: &mut EncodeStats,
+    ) -> (u8, u8) {
+        let pos_a = self.positions[a as usize];
+        let pos_b = self.positions[b as usize];
+        let forward = is_encode == (self.shift == ShiftDirection::RightDown);
+        match self.classify_pair(a, b) {
+            PairCase::SameCell => {
+                stats.same_cell_recursions += 1;
+                self.encode_or_decode_pair(a, Self::filler_after(self.pad, a), is_encode, stats)
+            }
+            PairCase::SameColumn => {
+                if forward {
+                    (
+                        self.letters[(pos_a + 8) as usize],
+                        self.letters[(pos_b + 8) as usize],
+                    )
+                } else {
+                    (
+                        self.letters[(pos_a - 8) as usize],
+                        self.letters[(pos_b - 8) as usize],
+                    )
+                }
+            }
+            PairCase::SameRow => {
+                if forward {
+                    (
+                        self.letters[(pos_a + 1) as usize],
+                        self.letters[(pos_b + 1) as usize],
+                    )
+                } else {
+                    (
+                        self.letters[(pos_a - 1) as usize],
+                        self.letters[(pos_b - 1) as usize],
+                    )
+                }
+            }
+            PairCase::Rectangle => {
+                let pos_c = (pos_a & 0o70) | (pos_b & 7);
+                let pos_d = (pos_b & 0o70) | (pos_a & 7);
+                (self.letters[pos_c as usize], self.letters[pos_d as usize])
+            }
+        }
+    }
+}
+
+/// What `encode_bytes` and `decode_bytes` do with results which are not
+/// valid UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Fail with the `FromUtf8Error`.
+    #[default]
+    Error,
+    /// Replace invalid sequences with U+FFFD REPLACEMENT CHARACTER.
+    Lossy,
+}
+
+/// Direction in which encoding shifts two letters of the same row or column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ShiftDirection {
+    /// Shift right within a row and down within a column, as usual.
+    #[default]
+    RightDown,
+    /// Shift left within a row and up within a column.
+    LeftUp,
+}
+
+// The rules of the cipher, by the relative position of the letters of a pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PairCase {
+    SameCell,
+    SameColumn,
+    SameRow,
+    Rectangle,
+}
+
+/// Outcome of [`PlayfairCipher::decode_repair`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RepairInfo {
+    /// Byte offset and value of the letter left out to reach an even length.
+    pub dropped: Option<(usize, char)>,
+    /// Byte offset and value of the letter inserted for `repaired`, see
+    /// [`PlayfairCipher::decode_repair`].
+    pub inserted: Option<(usize, char)>,
+    /// Decoding with the letter inserted where it was plausibly lost.
+    pub repaired: Option<String>,
+}
+
+/// Statistics about an encoding, returned by
+/// [`PlayfairCipher::encode_with_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncodeStats {
+    /// Number of pairs of two pad letters, which had to be completed with a
+    /// second filler instead.
+    pub same_cell_recursions: usize,
+}
+
+/// Settings and plaintext which reproduce a ciphertext exactly, created by
+/// [`PlayfairCipher::reproduction_recipe`].
+///
+/// # Example
+///
+/// ```
+/// use playfair::PlayfairCipher;
+/// let cipher = PlayfairCipher::new("gravity falls").with_pad('q');
+/// let recipe = cipher.reproduction_recipe("attack at dawn");
+/// assert_eq!(recipe.reproduce().unwrap(), cipher.encode("attack at dawn").unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recipe {
+    /// The letters of the square in row-major order.
+    pub grid: String,
+    /// The filler letter.
+    pub pad: char,
+    /// The alternating filler letters, see `with_pads`, or empty.
+    pub pads: Vec<char>,
+    pub shift: ShiftDirection,
+    pub plaintext: String,
+}
+
+impl Recipe {
+    /// Encodes the plaintext with the recorded settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pad` is not a lowercase ASCII letter.
+    pub fn reproduce(&self) -> Result<String, EncodeError> {
+        let mut cipher = PlayfairCipher::from_grid(&self.grid)?
+            .with_pad(self.pad)
+            .with_shift_direction(self.shift);
+        if !self.pads.is_empty() {
+            cipher = cipher.with_pads(&self.pads);
+        }
+        Ok(cipher.encode(&self.plaintext)?)
+    }
+}
+
+/// Result of [`PlayfairCipher::conformance_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConformanceReport {
+    /// One entry per combination of key, pad and shift direction.
+    pub cases: Vec<ConformanceCase>,
+}
+
+impl ConformanceReport {
+    /// Tells whether the roundtrip succeeded for all combinations.
+    pub fn all_passed(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+}
+
+/// A combination of settings checked by [`PlayfairCipher::conformance_report`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConformanceCase {
+    pub key: &'static str,
+    pub pad: char,
+    /// The alternating pads, or empty if only `pad` is used.
+    pub pads: &'static [char],
+    pub shift: ShiftDirection,
+    /// Whether all sample texts survived the roundtrip.
+    pub passed: bool,
+}
+
+/// Error returned by [`PlayfairCipher::from_grid`] and
+/// [`PlayfairCipher::from_ocr_letters`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridError {
+    /// The grid contains a character which is not a lowercase letter (not a
+    /// letter of either case for `from_ocr_letters`).
+    InvalidChar(char),
+    /// The letter occurs more than once ('i' and 'j' count as the same letter).
+    Duplicate(char),
+    /// The grid has this many letters instead of 25.
+    WrongLength(usize),
+    /// The grid has 24 distinct letters and lacks this one. Only reported by
+    /// `from_ocr_letters`; `from_grid` reports `WrongLength(24)`.
+    Missing(char),
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::InvalidChar(c) => write!(f, "invalid character {:?} in grid", c),
+            GridError::Duplicate(c) => write!(f, "letter {:?} occurs twice in grid", c),
+            GridError::WrongLength(count) => {
+                write!(f, "grid has {} letters instead of 25", count)
+            }
+            GridError::Missing(c) => write!(f, "letter {:?} is missing from grid", c),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// Error returned by the encoding methods which can fail for other reasons
+/// than invalid UTF-8.
+#[derive(Debug)]
+pub enum EncodeError {
+    /// The ciphertext would have `letters` letters, more than `max_letters`.
+    TooLong { letters: usize, max_letters: usize },
+    /// The square could not be built from the given grid.
+    InvalidGrid(GridError),
+    /// The ciphertext is not valid UTF-8.
+    Utf8(FromUtf8Error),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::TooLong {
+                letters,
+                max_letters,
+            } => write!(
+                f,
+                "ciphertext needs {} letters but only {} are allowed",
+                letters, max_letters
+            ),
+            EncodeError::InvalidGrid(err) => err.fmt(f),
+            EncodeError::Utf8(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EncodeError::TooLong { .. } => None,
+            EncodeError::InvalidGrid(err) => Some(err),
+            EncodeError::Utf8(err) => Some(err),
         }
     }
 }
 
+impl From<GridError> for EncodeError {
+    fn from(err: GridError) -> Self {
+        EncodeError::InvalidGrid(err)
+    }
+}
+
+impl From<FromUtf8Error> for EncodeError {
+    fn from(err: FromUtf8Error) -> Self {
+        EncodeError::Utf8(err)
+    }
+}
+
+/// Iterator over the digraphs of a text, created by [`PlayfairCipher::digraphs`].
+pub struct Digraphs<'a> {
+    bytes: std::str::Bytes<'a>,
+    // Second letter of a double, which starts the next digraph.
+    pending: Option<u8>,
+    pad: u8,
+    pads: &'a [u8],
+    // Number of digraphs returned so far.
+    count: usize,
+}
+
+impl Digraphs<'_> {
+    fn next_letter(&mut self) -> Option<u8> {
+        self.bytes.find_map(PlayfairCipher::letter_index)
+    }
+
+    // Returns the filler which completes the current digraph after `first`.
+    fn filler(&self, first: u8) -> u8 {
+        PlayfairCipher::filler_at(self.pad, self.pads, self.count, first)
+    }
+}
+
+impl Iterator for Digraphs<'_> {
+    type Item = (char, char);
+
+    fn next(&mut self) -> Option<(char, char)> {
+        let first = match self.pending.take() {
+            Some(letter) => letter,
+            None => self.next_letter()?,
+        };
+        let second = match self.next_letter() {
+            Some(letter) if letter == first => {
+                self.pending = Some(letter);
+                self.filler(first)
+            }
+            Some(letter) => letter,
+            None => self.filler(first),
+        };
+        self.count += 1;
+        Some((
+            PlayfairCipher::index_letter(first) as char,
+            PlayfairCipher::index_letter(second) as char,
+        ))
+    }
+}
+
 #[test]
 fn test_playfair_cipher_simple_test() {
     let cipher = PlayfairCipher::new("Hello Playfair Cipher");
@@ -222,3 +1446,775 @@ fn test_playfair_cipher_attack_at_dawn() {
     let b = cipher.encode(a).unwrap();
     assert_eq!(b, "gffgbm gf nfaw");
 }
+
+#[test]
+fn test_playfair_cipher_custom_pad() {
+    let cipher = PlayfairCipher::new("playfair example").with_pad('q');
+    let b = cipher.encode("balloon").unwrap();
+    assert_eq!(cipher.decode(&b).unwrap(), "balqloon");
+}
+
+#[test]
+fn test_playfair_cipher_digraphs() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let pairs: Vec<_> = cipher.digraphs("hide the gold in the tree stump").collect();
+    assert_eq!(
+        pairs,
+        [
+            ('h', 'i'),
+            ('d', 'e'),
+            ('t', 'h'),
+            ('e', 'g'),
+            ('o', 'l'),
+            ('d', 'i'),
+            ('n', 't'),
+            ('h', 'e'),
+            ('t', 'r'),
+            ('e', 'x'),
+            ('e', 's'),
+            ('t', 'u'),
+            ('m', 'p'),
+        ]
+    );
+}
+
+#[test]
+fn test_playfair_cipher_padding_count() {
+    let cipher = PlayfairCipher::new("playfair example");
+    assert_eq!(cipher.padding_count("hide the gold in the tree stump"), 1);
+    assert_eq!(cipher.padding_count("balloon"), 1);
+    assert_eq!(cipher.padding_count("tree"), 2);
+    assert_eq!(cipher.padding_count("hide"), 0);
+}
+
+#[test]
+fn test_playfair_cipher_encode_within() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let b = cipher.encode_within("tree", 6).unwrap();
+    assert_eq!(b, cipher.encode("tree").unwrap());
+    match cipher.encode_within("tree", 5) {
+        Err(EncodeError::TooLong {
+            letters: 6,
+            max_letters: 5,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_playfair_cipher_timed() {
+    let cipher = PlayfairCipher::new("gravity falls");
+    let a = "attack at dawn";
+    let day_one = cipher.encode_timed(a, 1).unwrap();
+    let day_two = cipher.encode_timed(a, 2).unwrap();
+    assert_ne!(day_one, day_two);
+    assert_ne!(day_one, cipher.encode(a).unwrap());
+    assert_eq!(cipher.decode_timed(&day_one, 1).unwrap(), a);
+    assert_eq!(cipher.decode_timed(&day_two, 2).unwrap(), a);
+    assert_ne!(cipher.decode_timed(&day_two, 1).unwrap(), a);
+    assert_eq!(cipher.encode_timed(a, 21).unwrap(), day_one);
+}
+
+#[test]
+fn test_playfair_cipher_decode_indices() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let indices = cipher.decode_indices("bmod zbx dnab ek udm uixmm ouvif");
+    let letters: String = indices
+        .iter()
+        .map(|&index| PlayfairCipher::index_letter(index) as char)
+        .collect();
+    assert_eq!(letters, "hidethegoldinthetrexestump");
+    assert_eq!(&indices[..2], &[7, 8]);
+}
+
+#[test]
+fn test_playfair_cipher_from_grid() {
+    let cipher = PlayfairCipher::from_grid("playfirexmbcdghknoqstuvwz").unwrap();
+    assert_eq!(
+        cipher.square(),
+        PlayfairCipher::new("playfair example").square()
+    );
+    assert_eq!(
+        PlayfairCipher::from_grid("playf jrexm bcdgh knoqs tuvwz")
+            .unwrap()
+            .square(),
+        cipher.square()
+    );
+    assert_eq!(
+        PlayfairCipher::from_grid("playf irexm bcdgh knoqs tuvw").err(),
+        Some(GridError::WrongLength(24))
+    );
+    assert_eq!(
+        PlayfairCipher::from_grid("playf irexm bcdgh knoqs tuvwj").err(),
+        Some(GridError::Duplicate('j'))
+    );
+    assert_eq!(
+        PlayfairCipher::from_grid("Playf irexm bcdgh knoqs tuvwz").err(),
+        Some(GridError::InvalidChar('P'))
+    );
+}
+
+#[test]
+fn test_playfair_cipher_encode_with_grid() {
+    let grid = "playf irexm bcdgh knoqs tuvwz";
+    let a = "hide the gold in the tree stump";
+    let b = PlayfairCipher::encode_with_grid(grid, a).unwrap();
+    assert_eq!(
+        b,
+        PlayfairCipher::from_grid(grid).unwrap().encode(a).unwrap()
+    );
+    assert_eq!(b, "bmod zbx dnab ek udm uixmm ouvif");
+    assert_eq!(
+        PlayfairCipher::decode_with_grid(grid, &b).unwrap(),
+        "hide the gold in the trexe stump"
+    );
+    assert!(matches!(
+        PlayfairCipher::encode_with_grid("abc", a),
+        Err(EncodeError::InvalidGrid(GridError::WrongLength(3)))
+    ));
+}
+
+#[test]
+fn test_playfair_cipher_runs_of_equal_letters() {
+    let cipher = PlayfairCipher::new("playfair example");
+    for (a, decoded) in [
+        ("aa", "axax"),
+        ("aaa", "axaxax"),
+        ("aaaa", "axaxaxax"),
+        ("aaaaa", "axaxaxaxax"),
+    ] {
+        let b = cipher.encode(a).unwrap();
+        assert_eq!(b.len(), 2 * a.len());
+        assert_eq!(cipher.decode(&b).unwrap(), decoded);
+        assert_eq!(cipher.padding_count(a), a.len());
+    }
+}
+
+#[test]
+fn test_playfair_cipher_runs_of_pad_letters() {
+    let cipher = PlayfairCipher::new("playfair example");
+    for (a, decoded) in [
+        ("x", "xq"),
+        ("xx", "xqxq"),
+        ("xxx", "xqxqxq"),
+        ("bxxb", "bxxb"),
+    ] {
+        let b = cipher.encode(a).unwrap();
+        assert!(b.bytes().all(|c| c.is_ascii_lowercase()));
+        assert_eq!(cipher.decode(&b).unwrap(), decoded);
+    }
+    let cipher = cipher.with_pad('q');
+    let b = cipher.encode("qq").unwrap();
+    assert_eq!(cipher.decode(&b).unwrap(), "qxqx");
+}
+
+#[test]
+fn test_playfair_cipher_inverse_positions() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let inverse = cipher.inverse_positions();
+    for (cell, &index) in inverse.iter().enumerate() {
+        let letter = PlayfairCipher::index_letter(index) as char;
+        assert_eq!(cipher.position(letter), Some((cell / 5, cell % 5)));
+    }
+    assert_eq!(cipher.position('j'), cipher.position('i'));
+    assert_eq!(cipher.position('P'), None);
+
+    // "ex" lies in a row, so encoding shifts both letters one cell right.
+    let b = cipher.encode("ex").unwrap();
+    let (row, col) = cipher.position('e').unwrap();
+    let shifted = inverse[row * 5 + (col + 1) % 5];
+    assert_eq!(b.as_bytes()[0], PlayfairCipher::index_letter(shifted));
+}
+
+// Golden (key, plaintext, ciphertext) triples pinning down the exact output of
+// `encode`. They cover double letters, doubled pads, odd lengths, characters
+// which are not lowercase letters and all three rules of the cipher.
+#[cfg(test)]
+const GOLDEN: &[(&str, &str, &str)] = &[
+    (
+        "playfair example",
+        "hide the gold in the tree stump",
+        "bmod zbx dnab ek udm uixmm ouvif",
+    ),
+    ("playfair example", "balloon", "dpyranqo"),
+    ("playfair example", "odd", "voge"),
+    ("playfair example", "x", "gw"),
+    (
+        "playfair example",
+        "xx marks the spot",
+        "gwm ilenk zbm oakwi",
+    ),
+    ("gravity falls", "attack at dawn", "gffgbm gf nfaw"),
+    (
+        "gravity falls",
+        "Hello, World! It's 9:30.",
+        "Hoeex, Wmvfe! Iy't 9:30.",
+    ),
+    (
+        "my own little secret",
+        "jolly jumping jackdaws",
+        "tyeqim rynfsyu rbrpbboez",
+    ),
+    (
+        "",
+        "abcdefghiklmnopqrstuvwxyz",
+        "bcdeakhikfmnoplustuqwxyzvy",
+    ),
+    (
+        "",
+        "the quick brown fox jumps over the lazy dog",
+        "sia utkeh gwmyl hny ktnlt nzas ukc qfvz ithw",
+    ),
+    ("zyxwvutsrqponmlkihgfedcba", "mississippi", "ognsthnsthnzok"),
+    ("monarchy", "instruments", "gatlmzclrqxa"),
+    ("keyword", "éclair über café", "élsbhd ückd rbiét"),
+    ("keyword", "", ""),
+    ("keyword", "a-b c.d", "b-c r.a"),
+];
+
+#[test]
+fn test_playfair_cipher_golden() {
+    let mut cases = Vec::new();
+    for &(key, a, b) in GOLDEN {
+        let cipher = PlayfairCipher::new(key);
+        assert_eq!(cipher.encode(a).unwrap(), b, "key {:?}, text {:?}", key, a);
+        let c = cipher.decode(b).unwrap();
+        assert_eq!(cipher.encode(&c).unwrap(), b, "key {:?}, text {:?}", key, a);
+        for (x, y) in cipher.digraphs(a) {
+            let x = PlayfairCipher::letter_index(x as u8).unwrap();
+            let y = PlayfairCipher::letter_index(y as u8).unwrap();
+            cases.push(cipher.classify_pair(x, y));
+        }
+    }
+    assert!(cases.contains(&PairCase::SameRow));
+    assert!(cases.contains(&PairCase::SameColumn));
+    assert!(cases.contains(&PairCase::Rectangle));
+}
+
+#[test]
+fn test_playfair_cipher_encode_compact() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let a = "  Hide, the GOLD...\n\tin the tree stump?!";
+    let b = cipher.encode_compact(a).unwrap();
+    let compact = " Hide the GOLD in the tree stump ";
+    assert_eq!(b, cipher.encode(compact).unwrap());
+    assert_eq!(cipher.decode(&b).unwrap(), compact);
+
+    let cipher = PlayfairCipher::new("gravity falls");
+    let a = "Attack at DAWN";
+    assert_eq!(cipher.encode_compact(a).unwrap(), "Alqfgbm gf DAWN");
+}
+
+#[test]
+fn test_playfair_cipher_is_border() {
+    let cipher = PlayfairCipher::new("playfair example");
+    assert!(cipher.is_border('p'));
+    assert!(cipher.is_border('z'));
+    assert!(cipher.is_border('m'));
+    assert!(!cipher.is_border('d'));
+    assert!(!cipher.is_border('r'));
+    assert!(!cipher.is_border('?'));
+}
+
+#[test]
+fn test_playfair_cipher_reproduction_recipe() {
+    let cipher = PlayfairCipher::new("playfair example").with_pad('z');
+    let a = "hide the gold in the tree stump";
+    let recipe = cipher.reproduction_recipe(a);
+    assert_eq!(recipe.grid, "playfirexmbcdghknoqstuvwz");
+    assert_eq!(recipe.pad, 'z');
+    assert_eq!(recipe.reproduce().unwrap(), cipher.encode(a).unwrap());
+    assert_ne!(
+        recipe.reproduce().unwrap(),
+        PlayfairCipher::new("playfair example").encode(a).unwrap()
+    );
+}
+
+#[test]
+fn test_playfair_cipher_encode_with_stats() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let a = "hide the gold in the tree stump";
+    let (b, stats) = cipher.encode_with_stats(a).unwrap();
+    assert_eq!(b, cipher.encode(a).unwrap());
+    assert_eq!(stats.same_cell_recursions, 0);
+
+    let (b, stats) = cipher.encode_with_stats("xx").unwrap();
+    assert_eq!(b, cipher.encode("xx").unwrap());
+    assert_eq!(stats.same_cell_recursions, 2);
+
+    let cipher = cipher.with_pad('e');
+    let (_, stats) = cipher.encode_with_stats(a).unwrap();
+    assert_eq!(stats.same_cell_recursions, 1);
+}
+
+#[test]
+fn test_playfair_cipher_encode_lookup_tables() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let (forward, inverse) = cipher.encode_lookup_tables();
+    assert_eq!(forward.len(), 1250);
+    assert_eq!(inverse.len(), 1250);
+    for a in 0..25u8 {
+        for b in (0..25u8).filter(|&b| b != a) {
+            let text: String = [a, b]
+                .iter()
+                .map(|&index| PlayfairCipher::index_letter(index) as char)
+                .collect();
+            let i = 2 * (25 * a as usize + b as usize);
+            let encoded = cipher.encode(&text).unwrap();
+            let expected = [forward[i], forward[i + 1]].map(PlayfairCipher::index_letter);
+            assert_eq!(encoded.as_bytes(), expected);
+
+            let c = forward[i] as usize;
+            let d = forward[i + 1] as usize;
+            let j = 2 * (25 * c + d);
+            assert_eq!([inverse[j], inverse[j + 1]], [a, b]);
+        }
+    }
+    // a doubled pad is completed with 'q', as in encoding "x"
+    let x = PlayfairCipher::X_INDEX as usize;
+    let i = 2 * (25 * x + x);
+    let expected = [forward[i], forward[i + 1]].map(PlayfairCipher::index_letter);
+    assert_eq!(cipher.encode("x").unwrap().as_bytes(), expected);
+    let b = cipher.encode("x").unwrap();
+    assert_eq!(cipher.decode(&b).unwrap(), "xq");
+}
+
+#[test]
+fn test_playfair_cipher_decode_repair() {
+    let cipher = PlayfairCipher::new("gravity falls");
+    let (c, info) = cipher.decode_repair("gffgbm gf nfaw").unwrap();
+    assert_eq!(c, "attack at dawn");
+    assert_eq!(info, RepairInfo::default());
+
+    // The last letter got lost.
+    let (c, info) = cipher.decode_repair("gffgbm gf nfa.").unwrap();
+    assert_eq!(c, "attack at da.");
+    assert_eq!(info.dropped, Some((12, 'a')));
+    assert_eq!(info.inserted, Some((13, 'b')));
+    // no letter after 'a' decodes to a digraph ending in the pad
+    assert_eq!(
+        info.repaired.unwrap(),
+        cipher.decode("gffgbm gf nfab.").unwrap()
+    );
+
+    // The second letter of a trailing pad digraph got lost.
+    let b = cipher.encode("attack at dawns").unwrap();
+    let (_, info) = cipher.decode_repair(&b[..b.len() - 1]).unwrap();
+    let repaired = info.repaired.unwrap();
+    assert_eq!(repaired.len(), "attack at dawnsx".len());
+    assert!(repaired.starts_with("attack at dawn") && repaired.ends_with('x'));
+
+    // A letter in the middle got lost, which leaves a digraph "ff".
+    let (_, info) = cipher.decode_repair("ffgbm gf nfaw").unwrap();
+    assert_eq!(info.dropped, Some((12, 'w')));
+    assert_eq!(info.inserted, Some((0, 'a')));
+    let repaired = info.repaired.unwrap();
+    assert!(repaired.ends_with("tack at dawn"), "{}", repaired);
+    assert_eq!(repaired, cipher.decode("affgbm gf nfaw").unwrap());
+}
+
+#[test]
+fn test_playfair_cipher_decode_unpadded_exact() {
+    let cipher = PlayfairCipher::new("playfair example");
+    for a in [
+        "hide the gold in the tree stump",
+        "fox exxit, xx!",
+        "odd!",
+        "xerox",
+        "",
+    ] {
+        let (b, mask) = cipher.encode_with_fillers(a).unwrap();
+        assert_eq!(b, cipher.encode(a).unwrap());
+        assert_eq!(
+            mask.iter().filter(|&&filler| filler).count(),
+            cipher.padding_count(a)
+        );
+        assert_eq!(cipher.decode_unpadded_exact(&b, &mask).unwrap(), a);
+    }
+}
+
+#[test]
+fn test_playfair_cipher_from_mnemonic() {
+    let words = ["Correct", "horse", "BATTERY", "staple!"];
+    let cipher = PlayfairCipher::from_mnemonic(&words);
+    assert_eq!(cipher.grid(), "corethsbaypldfgikmnquvwxz");
+    assert_eq!(cipher.grid(), PlayfairCipher::from_mnemonic(&words).grid());
+    assert_eq!(
+        cipher.grid(),
+        PlayfairCipher::new("correcthorsebatterystaple").grid()
+    );
+}
+
+#[test]
+fn test_playfair_cipher_is_identity() {
+    assert!(PlayfairCipher::new("").is_identity());
+    assert!(PlayfairCipher::new("ABC, abc").is_identity());
+    assert!(!PlayfairCipher::new("playfair").is_identity());
+    assert!(PlayfairCipher::new("aabc").is_identity());
+    assert!(!PlayfairCipher::new("abd").is_identity());
+}
+
+#[test]
+fn test_playfair_cipher_shift_direction() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let reversed = cipher.clone().with_shift_direction(ShiftDirection::LeftUp);
+    // "ex" shares a row and "de" a column, "hi" forms a rectangle.
+    assert_eq!(cipher.encode("ex de hi").unwrap(), "xm od bm");
+    assert_eq!(reversed.encode("ex de hi").unwrap(), "re ea bm");
+    assert_eq!(
+        reversed.encode("ex de hi").unwrap(),
+        cipher.decode("ex de hi").unwrap()
+    );
+
+    for a in ["hide the gold in the tree stump", "xx marks the spot"] {
+        let b = reversed.encode(a).unwrap();
+        assert_eq!(
+            reversed.decode(&b).unwrap(),
+            cipher.decode(&cipher.encode(a).unwrap()).unwrap()
+        );
+    }
+    let recipe = reversed.reproduction_recipe("ex");
+    assert_eq!(recipe.reproduce().unwrap(), "re");
+}
+
+#[test]
+fn test_playfair_cipher_config_fingerprint() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let fingerprint = cipher.config_fingerprint();
+    assert_eq!(
+        PlayfairCipher::new("playfair example").config_fingerprint(),
+        fingerprint
+    );
+
+    let mut square = cipher.square();
+    square.rotate_left(5);
+    let shifted_rows = cipher.with_square(&square);
+    assert_eq!(
+        shifted_rows.encode("hide the gold").unwrap(),
+        cipher.encode("hide the gold").unwrap()
+    );
+    assert_eq!(shifted_rows.config_fingerprint(), fingerprint);
+    for row in square.chunks_mut(5) {
+        row.rotate_left(2);
+    }
+    assert_eq!(
+        cipher.with_square(&square).config_fingerprint(),
+        fingerprint
+    );
+
+    assert_ne!(
+        PlayfairCipher::new("playfair").config_fingerprint(),
+        fingerprint
+    );
+    assert_ne!(
+        cipher.clone().with_pad('q').config_fingerprint(),
+        fingerprint
+    );
+    assert_ne!(
+        cipher
+            .clone()
+            .with_shift_direction(ShiftDirection::LeftUp)
+            .config_fingerprint(),
+        fingerprint
+    );
+
+    let mut square = cipher.square();
+    square.reverse();
+    let rotated = cipher
+        .with_square(&square)
+        .with_shift_direction(ShiftDirection::LeftUp);
+    for text in ["hide the gold in the tree stump", "balloon", "xx", "quiz"] {
+        assert_eq!(rotated.encode(text).unwrap(), cipher.encode(text).unwrap());
+    }
+    assert_eq!(rotated.config_fingerprint(), fingerprint);
+
+    assert_eq!(
+        cipher.clone().with_pads(&['x']).config_fingerprint(),
+        fingerprint
+    );
+    assert_eq!(
+        cipher
+            .clone()
+            .with_pads(&['x', 'x', 'x'])
+            .config_fingerprint(),
+        fingerprint
+    );
+    let alternating = cipher.clone().with_pads(&['z', 'q']);
+    assert_eq!(
+        cipher
+            .clone()
+            .with_pads(&['z', 'q', 'z', 'q'])
+            .config_fingerprint(),
+        alternating.config_fingerprint()
+    );
+    assert_ne!(alternating.config_fingerprint(), fingerprint);
+    assert_ne!(
+        cipher
+            .clone()
+            .with_pads(&['z', 'q', 'q'])
+            .config_fingerprint(),
+        alternating.config_fingerprint()
+    );
+}
+
+#[test]
+fn test_playfair_cipher_decode_spans() {
+    let cipher = PlayfairCipher::new("gravity falls");
+    let b = "g!f fgé a";
+    let c = cipher.decode(b).unwrap();
+    assert_eq!(c, "a!t taé vw");
+    let spans = cipher.decode_spans(b);
+    assert_eq!(
+        spans,
+        [
+            (0..1, 0..3),
+            (1..2, 1..2),
+            (2..3, 0..3),
+            (3..4, 3..4),
+            (4..5, 4..6),
+            (5..6, 4..6),
+            (6..8, 6..8),
+            (8..9, 8..9),
+            (9..10, 9..10),
+            (10..11, 9..10),
+        ]
+    );
+    let mut end = 0;
+    for (plain, _) in &spans {
+        assert_eq!(plain.start, end);
+        end = plain.end;
+    }
+    assert_eq!(end, c.len());
+
+    // double letters do not occur in valid ciphertexts but are split as usual
+    assert_eq!(
+        cipher.decode_spans("aa"),
+        [(0..1, 0..1), (1..2, 0..1), (2..3, 1..2), (3..4, 1..2)]
+    );
+}
+
+#[test]
+fn test_playfair_cipher_utf8_policy() {
+    let cipher = PlayfairCipher::new("gravity falls");
+    let a = b"attack \xff at dawn";
+    assert!(cipher.encode_bytes(a, Utf8Policy::Error).is_err());
+    let b = cipher.encode_bytes(a, Utf8Policy::Lossy).unwrap();
+    assert_eq!(b, "gffgbm \u{fffd} gf nfaw");
+    let c = cipher
+        .decode_bytes(b"gffgbm \xff gf nfaw", Utf8Policy::Lossy)
+        .unwrap();
+    assert_eq!(c, "attack \u{fffd} at dawn");
+    assert_eq!(
+        cipher
+            .encode_bytes(b"attack at dawn", Utf8Policy::Error)
+            .unwrap(),
+        "gffgbm gf nfaw"
+    );
+}
+
+#[test]
+fn test_playfair_cipher_new_with_fill_iter() {
+    let cipher = PlayfairCipher::new_with_fill_iter("", "etaoinETAOIN".chars());
+    assert_eq!(cipher.grid(), "etaoinbcdfghklmpqrsuvwxyz");
+    let cipher = PlayfairCipher::new_with_fill_iter("key", ('a'..='z').rev());
+    assert_eq!(cipher.grid(), "keyzxwvutsrqponmlihgfdcba");
+    assert_eq!(
+        PlayfairCipher::new_with_fill_iter("key", 'a'..='z').grid(),
+        PlayfairCipher::new("key").grid()
+    );
+}
+
+#[test]
+fn test_playfair_cipher_encode_padded_to() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let alternating = cipher.clone().with_pads(&['z', 'q']);
+    for cipher in [&cipher, &alternating] {
+        for a in [
+            "attack at dawn",
+            "xerox",
+            "hide the gold",
+            "tree",
+            "qx",
+            "balloon",
+            "",
+        ] {
+            for target in [14, 15, 20] {
+                let (b, padding) = cipher.encode_padded_to(a, target).unwrap();
+                let letters = b.bytes().filter(u8::is_ascii_lowercase).count();
+                assert_eq!(letters, target / 2 * 2, "text {:?}, target {}", a, target);
+                let expected = cipher.decode(&cipher.encode(a).unwrap()).unwrap();
+                assert_eq!(cipher.decode_padded(&b, padding).unwrap(), expected);
+            }
+        }
+    }
+    let (b, padding) = alternating.encode_padded_to("balloon", 14).unwrap();
+    assert_eq!(alternating.decode_padded(&b, padding).unwrap(), "balqloon");
+    assert!(matches!(
+        cipher.encode_padded_to("attack at dawn", 10),
+        Err(EncodeError::TooLong {
+            letters: 12,
+            max_letters: 10,
+        })
+    ));
+}
+
+#[test]
+fn test_playfair_cipher_new_verbose() {
+    let (cipher, skipped) = PlayfairCipher::new_verbose("hello world");
+    assert_eq!(cipher.grid(), PlayfairCipher::new("hello world").grid());
+    assert_eq!(skipped, ['l', ' ', 'o', 'l']);
+
+    let (_, skipped) = PlayfairCipher::new_verbose("Iijé");
+    assert_eq!(skipped, ['I', 'j', 'é']);
+}
+
+#[test]
+fn test_playfair_cipher_encode_parts() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let parts = ["hide", "", "the gold", "", "x"];
+    let encoded = cipher.encode_parts(&parts).unwrap();
+    assert_eq!(encoded.len(), parts.len());
+    assert_eq!(encoded[0], "bmod");
+    assert_eq!(encoded[1], "");
+    assert_eq!(
+        format!("{}{}", encoded[0], encoded[2]),
+        cipher.encode("hidethe gold").unwrap()
+    );
+    let encoded: Vec<&str> = encoded.iter().map(String::as_str).collect();
+    assert_eq!(
+        cipher.decode_parts(&encoded).unwrap(),
+        ["hide", "", "the goldx", "", "xq"]
+    );
+}
+
+#[test]
+fn test_playfair_cipher_encode_each() {
+    let cipher = PlayfairCipher::new("playfair example");
+    let text = "hide the gold in the tree stump";
+    let mut pairs = Vec::new();
+    cipher.encode_each(text, |plain, encoded| pairs.push((plain, encoded)));
+    let encoded: Vec<char> = cipher
+        .encode(text)
+        .unwrap()
+        .chars()
+        .filter(char::is_ascii_lowercase)
+        .collect();
+    let expected: Vec<_> = cipher
+        .digraphs(text)
+        .zip(encoded.chunks(2))
+        .map(|(plain, encoded)| (plain, (encoded[0], encoded[1])))
+        .collect();
+    assert_eq!(pairs, expected);
+    assert_eq!(pairs[0], (('h', 'i'), ('b', 'm')));
+    assert_eq!(pairs[9], (('e', 'x'), ('x', 'm')));
+}
+
+#[test]
+fn test_playfair_cipher_conformance_report() {
+    let report = PlayfairCipher::conformance_report();
+    assert_eq!(report.cases.len(), 4 * (25 + 2) * 2);
+    assert!(report.all_passed());
+    assert!(report.cases.iter().any(|case| case.pads == ['z', 'q']));
+}
+
+#[test]
+fn test_playfair_cipher_with_pads() {
+    let cipher = PlayfairCipher::new("playfair example").with_pads(&['x', 'z', 'q']);
+    assert_eq!(cipher.pad(), 'x');
+    let pairs: Vec<_> = cipher.digraphs("aaaa").collect();
+    assert_eq!(pairs, [('a', 'x'), ('a', 'z'), ('a', 'q'), ('a', 'x')]);
+
+    for text in [
+        "aaaa",
+        "hide the gold in the tree stump",
+        "balloon feels too tall",
+    ] {
+        let (encoded, mask) = cipher.encode_with_fillers(text).unwrap();
+        let letters: String = cipher
+            .decode(&encoded)
+            .unwrap()
+            .chars()
+            .filter(char::is_ascii_lowercase)
+            .collect();
+        let expected: String = cipher.digraphs(text).flat_map(|(a, b)| [a, b]).collect();
+        assert_eq!(letters, expected);
+        assert_eq!(cipher.decode_unpadded_exact(&encoded, &mask).unwrap(), text);
+    }
+
+    // a filler equal to the letter it completes is replaced as usual
+    let (encoded, mask) = cipher.encode_with_fillers("abzz xx").unwrap();
+    assert_eq!(cipher.decode(&encoded).unwrap(), "abzxz xxq");
+    assert_eq!(
+        cipher.decode_unpadded_exact(&encoded, &mask).unwrap(),
+        "abzz xx"
+    );
+
+    let text = "balloon";
+    let recipe = cipher.reproduction_recipe(text);
+    assert_eq!(recipe.pads, ['x', 'z', 'q']);
+    assert_eq!(recipe.reproduce().unwrap(), cipher.encode(text).unwrap());
+    assert_ne!(
+        cipher.config_fingerprint(),
+        cipher.clone().with_pad('x').config_fingerprint()
+    );
+}
+
+#[test]
+fn test_playfair_cipher_from_ocr_letters() {
+    let letters: Vec<char> = "PLAYF IREXM BCDGH KNOQS TUVWZ".chars().collect();
+    let cipher = PlayfairCipher::from_ocr_letters(&letters).unwrap();
+    assert_eq!(cipher.grid(), "playfirexmbcdghknoqstuvwz");
+    let letters: Vec<char> = "playfjrexmbcdghknoqstuvwz".chars().collect();
+    assert_eq!(
+        PlayfairCipher::from_ocr_letters(&letters).unwrap().grid(),
+        cipher.grid()
+    );
+
+    // 'o' misread as 'c'
+    let letters: Vec<char> = "playfirexmbcdghkncqstuvwz".chars().collect();
+    assert_eq!(
+        PlayfairCipher::from_ocr_letters(&letters).err(),
+        Some(GridError::Duplicate('c'))
+    );
+    // 'o' not recognized
+    let letters: Vec<char> = "playfirexmbcdghknqstuvwz".chars().collect();
+    assert_eq!(
+        PlayfairCipher::from_ocr_letters(&letters).err(),
+        Some(GridError::Missing('o'))
+    );
+    let letters: Vec<char> = "playfrexmbcdghknqstuvwz".chars().collect();
+    assert_eq!(
+        PlayfairCipher::from_ocr_letters(&letters).err(),
+        Some(GridError::WrongLength(23))
+    );
+    // '0' read instead of 'o'
+    let letters: Vec<char> = "playfirexmbcdghkn0qstuvwz".chars().collect();
+    assert_eq!(
+        PlayfairCipher::from_ocr_letters(&letters).err(),
+        Some(GridError::InvalidChar('0'))
+    );
+}
+
+#[test]
+fn test_playfair_cipher_sharp_s() {
+    let cipher = PlayfairCipher::new("playfair example");
+    for (text, expected) in [
+        ("die straße", "die straße"),
+        ("Fuß", "Fußx"),
+        ("fuß", "fußx"),
+        ("weiß und groß ist", "weiß und groß ist"),
+        ("ß", "ßx"),
+    ] {
+        let encoded = cipher.encode_sharp_s(text).unwrap();
+        assert!(!encoded.contains('ß'));
+        assert_eq!(cipher.decode_sharp_s(&encoded).unwrap(), expected);
+    }
+    // a genuine double s is taken for an 'ß'
+    let encoded = cipher.encode_sharp_s("wasser").unwrap();
+    assert_eq!(cipher.decode_sharp_s(&encoded).unwrap(), "waßerx");
+
+    let cipher = cipher.with_pads(&['z', 'q']);
+    let encoded = cipher.encode_sharp_s("aßeß").unwrap();
+    assert_eq!(cipher.decode(&encoded).unwrap(), "asseszsq");
+    assert_eq!(cipher.decode_sharp_s(&encoded).unwrap(), "aßeßq");
+}
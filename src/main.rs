@@ -1,6 +1,25 @@
+use std::env;
+use std::io::{self, Read};
+use std::process;
+use std::time::Instant;
+
 use playfair::PlayfairCipher;
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => demo(),
+        Some("split") => split(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        Some(command) => {
+            eprintln!("unknown command: {}", command);
+            eprintln!("usage: playfair [split [TEXT]... | bench [MEGABYTES]]");
+            process::exit(2);
+        }
+    }
+}
+
+fn demo() {
     let key = "my own little secret";
     let cipher = PlayfairCipher::new(key);
     let encrypted_message = "Rzie tt debtnwl. Dwm'e veseqt cmowmb!w";
@@ -15,3 +34,52 @@ fn main() {
             .collect::<String>()
     );
 }
+
+// Prints the digraphs of the text given as arguments (or read from stdin),
+// showing where fillers are inserted.
+fn split(args: &[String]) {
+    let text = if args.is_empty() {
+        let mut text = String::new();
+        if let Err(err) = io::stdin().read_to_string(&mut text) {
+            eprintln!("failed to read stdin: {}", err);
+            process::exit(1);
+        }
+        text
+    } else {
+        args.join(" ")
+    };
+    let cipher = PlayfairCipher::new("");
+    let pairs: Vec<String> = cipher
+        .digraphs(&text)
+        .map(|(a, b)| format!("{}{}", a, b))
+        .collect();
+    println!("{}", pairs.join(" "));
+}
+
+// Encodes a generated text of the given size (4 MB by default) and prints the
+// throughput.
+fn bench(args: &[String]) {
+    let megabytes = match args.first().map(|arg| arg.parse::<usize>()) {
+        None => 4,
+        Some(Ok(megabytes)) if megabytes > 0 => megabytes,
+        Some(_) => {
+            eprintln!("invalid size: {}", args[0]);
+            process::exit(2);
+        }
+    };
+    let sample = "the quick brown fox jumps over the lazy dog. ";
+    let len = megabytes * 1_000_000;
+    let text: String = sample.chars().cycle().take(len).collect();
+    let cipher = PlayfairCipher::new("my own little secret");
+
+    let start = Instant::now();
+    let encoded = cipher.encode(&text).unwrap();
+    let seconds = start.elapsed().as_secs_f64();
+    assert!(encoded.len() >= text.len());
+    println!(
+        "encoded {} MB in {:.3} s: {:.1} MB/s",
+        megabytes,
+        seconds,
+        megabytes as f64 / seconds.max(1e-9)
+    );
+}
@@ -0,0 +1,104 @@
+use std::string::FromUtf8Error;
+
+use crate::PlayfairCipher;
+
+/// Decodes ciphertexts made of consecutive segments enciphered under
+/// different keys.
+///
+/// Each segment is given as a cipher and the number of letters it spans.
+/// Characters which are not letters belong to the segment of the letter
+/// preceding them, and any letters beyond the total length of all segments
+/// belong to the last one.
+///
+/// # Example
+///
+/// ```
+/// use playfair::{MultiKeyDecoder, PlayfairCipher};
+/// let first = PlayfairCipher::new("first key");
+/// let second = PlayfairCipher::new("second key");
+/// let text = first.encode("attack").unwrap() + &second.encode(" at dawn").unwrap();
+/// let decoder = MultiKeyDecoder::new(vec![(first, 6), (second, 6)]);
+/// assert_eq!(decoder.decode(&text).unwrap(), "attack at dawn");
+/// ```
+pub struct MultiKeyDecoder {
+    segments: Vec<(PlayfairCipher, usize)>,
+}
+
+impl MultiKeyDecoder {
+    /// Creates a decoder for the given segments.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segments` is empty, since decoding would then pass the
+    /// ciphertext off as plaintext, or if a segment other than the last one
+    /// has an odd number of letters. Encoding completes such a segment with
+    /// a pad, so its ciphertext is one letter longer than given.
+    pub fn new(segments: Vec<(PlayfairCipher, usize)>) -> Self {
+        assert!(!segments.is_empty(), "at least one segment is needed");
+        assert!(
+            segments[..segments.len() - 1]
+                .iter()
+                .all(|(_, letter_length)| letter_length % 2 == 0),
+            "segment lengths must be even"
+        );
+        Self { segments }
+    }
+
+    pub fn decode(&self, text: &str) -> Result<String, FromUtf8Error> {
+        let mut result = String::with_capacity(text.len() + 1);
+        let mut rest = text;
+        for (i, (cipher, letter_length)) in self.segments.iter().enumerate() {
+            let end = if i + 1 == self.segments.len() {
+                rest.len()
+            } else {
+                Self::segment_end(rest, *letter_length)
+            };
+            let (segment, tail) = rest.split_at(end);
+            result.push_str(&cipher.decode(segment)?);
+            rest = tail;
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    // Returns the byte offset of the first letter after the first
+    // `letter_length` letters of `text`.
+    fn segment_end(text: &str, letter_length: usize) -> usize {
+        let mut letters = 0;
+        for (offset, c) in text.bytes().enumerate() {
+            if PlayfairCipher::letter_index(c).is_some() {
+                if letters == letter_length {
+                    return offset;
+                }
+                letters += 1;
+            }
+        }
+        text.len()
+    }
+}
+
+#[test]
+fn test_multi_key_decoder_two_segments() {
+    let first = PlayfairCipher::new("playfair example");
+    let second = PlayfairCipher::new("gravity falls");
+    let a = "hide the gold";
+    let b = ", attack at dawn.";
+    let encoded_a = first.encode(a).unwrap();
+    let encoded_b = second.encode(b).unwrap();
+    let decoder = MultiKeyDecoder::new(vec![(first, 12), (second, 12)]);
+    let decoded = decoder.decode(&(encoded_a + &encoded_b)).unwrap();
+    assert_eq!(decoded, "hide the goldx, attack at dawn.");
+}
+
+#[test]
+#[should_panic(expected = "at least one segment is needed")]
+fn test_multi_key_decoder_no_segments() {
+    MultiKeyDecoder::new(Vec::new());
+}
+
+#[test]
+#[should_panic(expected = "segment lengths must be even")]
+fn test_multi_key_decoder_odd_segment() {
+    let cipher = PlayfairCipher::new("playfair example");
+    MultiKeyDecoder::new(vec![(cipher.clone(), 11), (cipher, 12)]);
+}
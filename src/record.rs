@@ -0,0 +1,249 @@
+use std::fmt;
+use std::string::FromUtf8Error;
+
+use crate::{GridError, PlayfairCipher, ShiftDirection};
+
+// Identifies a record and the version of its layout.
+const MAGIC: &[u8; 4] = b"PFR1";
+// Only 5x5 squares exist so far.
+const GRID_SIZE: u8 = 5;
+// Flag set if encoding shifts left and up.
+const FLAG_LEFT_UP: u8 = 1;
+
+/// Error returned by [`PlayfairCipher::encode_record`] and
+/// [`PlayfairCipher::decode_record`].
+#[derive(Debug)]
+pub enum RecordError {
+    /// The cipher alternates between this many pads, more than the 255 a
+    /// record can hold.
+    TooManyPads(usize),
+    /// The record does not start with the magic bytes.
+    BadMagic,
+    /// The record ends within the header.
+    Truncated,
+    /// The square of the record has a size other than 5.
+    UnsupportedGridSize(u8),
+    /// A filler in the header is not a lowercase letter.
+    InvalidPad(u8),
+    /// The flags byte has unknown bits set.
+    UnknownFlags(u8),
+    /// The square in the header is not valid.
+    InvalidGrid(GridError),
+    /// The ciphertext or the plaintext is not valid UTF-8.
+    Utf8(FromUtf8Error),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::TooManyPads(count) => {
+                write!(f, "{} pads do not fit into a record", count)
+            }
+            RecordError::BadMagic => write!(f, "not a Playfair record"),
+            RecordError::Truncated => write!(f, "record header is truncated"),
+            RecordError::UnsupportedGridSize(size) => {
+                write!(f, "unsupported grid size {}", size)
+            }
+            RecordError::InvalidPad(pad) => write!(f, "invalid pad byte {:#04x}", pad),
+            RecordError::UnknownFlags(flags) => write!(f, "unknown flags {:#04x}", flags),
+            RecordError::InvalidGrid(err) => err.fmt(f),
+            RecordError::Utf8(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordError::InvalidGrid(err) => Some(err),
+            RecordError::Utf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<GridError> for RecordError {
+    fn from(err: GridError) -> Self {
+        RecordError::InvalidGrid(err)
+    }
+}
+
+impl From<FromUtf8Error> for RecordError {
+    fn from(err: FromUtf8Error) -> Self {
+        RecordError::Utf8(err)
+    }
+}
+
+impl PlayfairCipher {
+    /// Encodes `text` into a record which also describes the cipher, so that
+    /// `decode_record` needs nothing else to decode it.
+    ///
+    /// The record consists of
+    ///
+    /// - the magic bytes `PFR1`,
+    /// - the grid size (always 5),
+    /// - a flags byte, with bit 0 set for [`ShiftDirection::LeftUp`],
+    /// - the pad letter,
+    /// - the number of alternating pads (see `with_pads`, 0 if not set, at
+    ///   most 255) and the pads themselves,
+    /// - the 25 letters of the square in row-major order,
+    /// - the ciphertext as UTF-8, up to the end of the record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use playfair::PlayfairCipher;
+    /// let cipher = PlayfairCipher::new("playfair example");
+    /// let record = cipher.encode_record("hide").unwrap();
+    /// assert_eq!(record.len(), 4 + 4 + 25 + 4);
+    /// assert!(record.ends_with(b"bmod"));
+    /// let (_, text) = PlayfairCipher::decode_record(&record).unwrap();
+    /// assert_eq!(text, "hide");
+    /// ```
+    pub fn encode_record(&self, text: &str) -> Result<Vec<u8>, RecordError> {
+        let pad_count =
+            u8::try_from(self.pads.len()).map_err(|_| RecordError::TooManyPads(self.pads.len()))?;
+        let encoded = self.encode(text)?;
+        let mut record = Vec::with_capacity(MAGIC.len() + 4 + self.pads.len() + 25 + encoded.len());
+        record.extend(MAGIC);
+        record.push(GRID_SIZE);
+        record.push(match self.shift {
+            ShiftDirection::RightDown => 0,
+            ShiftDirection::LeftUp => FLAG_LEFT_UP,
+        });
+        record.push(self.pad() as u8);
+        record.push(pad_count);
+        record.extend(self.pads.iter().map(|&pad| Self::index_letter(pad)));
+        record.extend(self.grid().bytes());
+        record.extend(encoded.bytes());
+        Ok(record)
+    }
+
+    /// Reads a record written by `encode_record` and returns the cipher it
+    /// describes together with the decoded text.
+    pub fn decode_record(record: &[u8]) -> Result<(Self, String), RecordError> {
+        let mut rest = record;
+        let mut take = |len: usize| {
+            if rest.len() < len {
+                return Err(RecordError::Truncated);
+            }
+            let (head, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(head)
+        };
+        if !record.starts_with(MAGIC) {
+            return Err(if record.len() < MAGIC.len() {
+                RecordError::Truncated
+            } else {
+                RecordError::BadMagic
+            });
+        }
+        take(MAGIC.len())?;
+        let header = take(4)?;
+        let (size, flags, pad, pad_count) = (header[0], header[1], header[2], header[3]);
+        if size != GRID_SIZE {
+            return Err(RecordError::UnsupportedGridSize(size));
+        }
+        if flags & !FLAG_LEFT_UP != 0 {
+            return Err(RecordError::UnknownFlags(flags));
+        }
+        let pads = take(pad_count as usize)?;
+        if let Some(&pad) = std::iter::once(&pad)
+            .chain(pads)
+            .find(|pad| !pad.is_ascii_lowercase())
+        {
+            return Err(RecordError::InvalidPad(pad));
+        }
+        let grid = String::from_utf8(take(25)?.to_vec())?;
+        let shift = if flags & FLAG_LEFT_UP != 0 {
+            ShiftDirection::LeftUp
+        } else {
+            ShiftDirection::RightDown
+        };
+        let mut cipher = Self::from_grid(&grid)?
+            .with_pad(pad as char)
+            .with_shift_direction(shift);
+        if !pads.is_empty() {
+            let pads: Vec<char> = pads.iter().map(|&pad| pad as char).collect();
+            cipher = cipher.with_pads(&pads);
+        }
+        let text = cipher.decode(&String::from_utf8(rest.to_vec())?)?;
+        Ok((cipher, text))
+    }
+}
+
+#[test]
+fn test_record_roundtrip() {
+    let cipher = PlayfairCipher::new("playfair example")
+        .with_pads(&['z', 'q'])
+        .with_shift_direction(ShiftDirection::LeftUp);
+    let text = "hide the gold in the tree stump";
+    let record = cipher.encode_record(text).unwrap();
+    assert!(record.starts_with(b"PFR1\x05\x01z\x02zqplayfirexm"));
+    let (decoded_cipher, decoded) = PlayfairCipher::decode_record(&record).unwrap();
+    assert_eq!(decoded_cipher, cipher);
+    assert_eq!(
+        decoded,
+        cipher.decode(&cipher.encode(text).unwrap()).unwrap()
+    );
+
+    // repeated pads are stored once
+    let cipher = PlayfairCipher::new("gravity falls").with_pads(&['z', 'q'].repeat(200));
+    let record = cipher.encode_record("attack at dawn").unwrap();
+    assert!(record.starts_with(b"PFR1\x05\x00z\x02zq"));
+    let (decoded_cipher, decoded) = PlayfairCipher::decode_record(&record).unwrap();
+    assert_eq!(decoded_cipher, cipher);
+    assert_eq!(decoded, "attack at dawn");
+
+    let mut pads = vec!['z'; 255];
+    pads.push('q');
+    let cipher = PlayfairCipher::new("gravity falls").with_pads(&pads);
+    assert!(matches!(
+        cipher.encode_record("attack at dawn"),
+        Err(RecordError::TooManyPads(256))
+    ));
+
+    let cipher = PlayfairCipher::new("");
+    let record = cipher.encode_record("").unwrap();
+    assert_eq!(record.len(), 4 + 4 + 25);
+    let (decoded_cipher, decoded) = PlayfairCipher::decode_record(&record).unwrap();
+    assert_eq!(decoded_cipher, cipher);
+    assert_eq!(decoded, "");
+}
+
+#[test]
+fn test_record_errors() {
+    let record = PlayfairCipher::new("playfair example")
+        .encode_record("hide")
+        .unwrap();
+    assert!(matches!(
+        PlayfairCipher::decode_record(b"PF"),
+        Err(RecordError::Truncated)
+    ));
+    assert!(matches!(
+        PlayfairCipher::decode_record(b"ZIP!\x05\x00x\x00"),
+        Err(RecordError::BadMagic)
+    ));
+    assert!(matches!(
+        PlayfairCipher::decode_record(&record[..20]),
+        Err(RecordError::Truncated)
+    ));
+    let mut bad = record.clone();
+    bad[4] = 6;
+    assert!(matches!(
+        PlayfairCipher::decode_record(&bad),
+        Err(RecordError::UnsupportedGridSize(6))
+    ));
+    let mut bad = record.clone();
+    bad[6] = b'X';
+    assert!(matches!(
+        PlayfairCipher::decode_record(&bad),
+        Err(RecordError::InvalidPad(b'X'))
+    ));
+    let mut bad = record.clone();
+    bad[9] = b'p';
+    assert!(matches!(
+        PlayfairCipher::decode_record(&bad),
+        Err(RecordError::InvalidGrid(GridError::Duplicate('p')))
+    ));
+}
@@ -0,0 +1,30 @@
+use std::process::Command;
+
+#[test]
+fn test_cli_split() {
+    let output = Command::new(env!("CARGO_BIN_EXE_playfair"))
+        .args(["split", "balloon", "tree"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "ba lx lo on tr ex ex\n"
+    );
+}
+
+#[test]
+fn test_cli_bench() {
+    let output = Command::new(env!("CARGO_BIN_EXE_playfair"))
+        .args(["bench", "1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let throughput = stdout
+        .trim_end()
+        .strip_suffix(" MB/s")
+        .and_then(|line| line.rsplit(' ').next())
+        .unwrap();
+    assert!(throughput.parse::<f64>().unwrap() > 0.0);
+}